@@ -1,9 +1,28 @@
 use clap::{arg, Command};
+use mumba_core::config::{Config, UpdateChannel};
+use mumba_core::download_cache::DownloadCache;
 use mumba_core::game::env::Env;
-use mumba_core::game::installation::{Edition, Installation};
+use mumba_core::game::ffnx_config::FfnxConfig;
+use mumba_core::game::ffnx_installation::FfnxInstallation;
+use mumba_core::game::install_journal::{InstallJournal, State};
+use mumba_core::game::installation::{Edition, Feature, Installation};
+use mumba_core::game::installer::{
+    Changelog, FfnxRelease, InstallEvent, InstallListener, Installer,
+};
+use mumba_core::game::verify;
+use mumba_core::github::GitHubRelease;
+use mumba_core::http;
+use mumba_core::markdown;
+use mumba_core::provision;
+use mumba_core::self_update::{self, SelfUpdateResult};
+use std::cell::Cell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 include!(concat!(env!("OUT_DIR"), "/built.rs"));
 
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
 fn cli() -> Command {
     Command::new("mmb")
         .version(GIT_VERSION)
@@ -11,31 +30,650 @@ fn cli() -> Command {
         .subcommand_required(true)
         .arg_required_else_help(true)
         .allow_external_subcommands(true)
+        .arg(arg!(--profile <NAME> "The game profile to use, instead of the current one").global(true))
         .subcommand(
             Command::new("replace_launcher")
                 .about("Replaces the launcher")
                 .arg(arg!(<APP_PATH> "The app path of the game"))
                 .arg_required_else_help(true),
         )
+        .subcommand(
+            Command::new("search").about("Lists the game installations found on this computer"),
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Checks the game files and looks for DLLs of other mods conflicting with FFNx"),
+        )
+        .subcommand(
+            Command::new("setup")
+                .about("Selects the game to configure")
+                .arg(arg!(<EXE_PATH> "The path to the FF8 executable"))
+                .arg(
+                    arg!(--"update-channel" <CHANNEL> "The FFNx update channel")
+                        .value_parser(["stable", "beta", "alpha"]),
+                )
+                .arg(arg!(--language <LANGUAGE> "The language of Mumba (en-US, fr-FR)"))
+                .arg(arg!(--"ffnx-path" <PATH> "The directory where FFNx is installed"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(
+            Command::new("install")
+                .about("Installs FFNx and prepares the game")
+                .arg(arg!(--"from-zip" <ZIP> "Installs FFNx from a local archive")),
+        )
+        .subcommand(
+            Command::new("upgrade")
+                .about("Upgrades FFNx to the last version of the update channel")
+                .arg(arg!(--"from-zip" <ZIP> "Upgrades FFNx from a local archive"))
+                .arg(arg!(--"dry-run" "Shows the notes of the releases to install, without upgrading"))
+                .arg(arg!(--force "Reinstalls FFNx even when it is up to date")),
+        )
+        .subcommand(Command::new("releases").about("Lists the FFNx releases"))
+        .subcommand(
+            Command::new("pin")
+                .about(
+                    "Installs this FFNx release on upgrade instead of following the update channel",
+                )
+                .arg(arg!(<TAG> "The FFNx release tag"))
+                .arg_required_else_help(true),
+        )
+        .subcommand(Command::new("unpin").about("Follows the update channel again"))
+        .subcommand(
+            Command::new("rollback")
+                .about("Restores the FFNx version replaced by the last upgrade"),
+        )
+        .subcommand(
+            Command::new("uninstall")
+                .about("Removes FFNx and puts back the game files it replaced"),
+        )
+        .subcommand(
+            Command::new("self-update")
+                .about("Updates Mumba itself, following the update channel")
+                .arg(arg!(--check "Only tells whether an update is available")),
+        )
+        .subcommand(Command::new("launch").about("Launches the game with FFNx"))
+        .subcommand(Command::new("launch-cw").about("Launches Chocobo World"))
+        .subcommand(
+            Command::new("cache")
+                .about("Manages the downloaded archives")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommand(Command::new("list").about("Lists the cached archives"))
+                .subcommand(Command::new("clear").about("Removes the cached archives")),
+        )
+        .subcommand(
+            Command::new("journal")
+                .about("Shows the steps of the last install, with the SHA-256 of the files before and after")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommand(Command::new("show").about("Prints the steps of the last install"))
+                .subcommand(
+                    Command::new("undo")
+                        .about("Puts back the files changed by an interrupted install"),
+                ),
+        )
+        .subcommand(
+            Command::new("profile")
+                .about("Manages the game profiles, each one has its own game, FFNx directory and update channel")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommand(Command::new("list").about("Lists the game profiles"))
+                .subcommand(
+                    Command::new("use")
+                        .about("Selects the current game profile, it is created on setup")
+                        .arg(arg!(<NAME> "The profile name")),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Removes a game profile")
+                        .arg(arg!(<NAME> "The profile name")),
+                ),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Reads or writes the FFNx configuration")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommand(
+                    Command::new("get")
+                        .about("Prints a FFNx configuration entry")
+                        .arg(arg!(<KEY> "The configuration key")),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about("Modifies a FFNx configuration entry")
+                        .arg(arg!(<KEY> "The configuration key"))
+                        .arg(arg!(<VALUE> "The new value")),
+                ),
+        )
+}
+
+/// `profile` is the one selected with `--profile`, instead of the current one of mumba.toml
+fn open_mumba_config(env: &Env, profile: Option<&str>) -> Config {
+    let mut mumba_config = Config::from_file(&env.config_path).unwrap_or_else(|_| Config::new());
+    if let Some(profile) = profile {
+        mumba_config.use_profile(profile)
+    }
+    mumba_config
+}
+
+fn retrieve_installation(
+    env: &Env,
+    profile: Option<&str>,
+) -> Result<(Config, Installation), Box<dyn std::error::Error>> {
+    let mumba_config = open_mumba_config(env, profile);
+    match mumba_config.installation()? {
+        Some(installation) => {
+            if installation.is_updating() {
+                eprintln!("Steam is updating the game, its files may be incomplete");
+            }
+            Ok((mumba_config, installation))
+        }
+        None => Err("No game configured, please run \"mmb setup <EXE_PATH>\" first".into()),
+    }
+}
+
+fn retrieve_ffnx_installation(
+    env: &Env,
+    profile: Option<&str>,
+) -> Result<(Installation, FfnxInstallation), Box<dyn std::error::Error>> {
+    let (mumba_config, installation) = retrieve_installation(env, profile)?;
+    let ffnx_installation = find_ffnx_installation(&mumba_config, &installation)?;
+    Ok((installation, ffnx_installation))
+}
+
+fn find_ffnx_installation(
+    mumba_config: &Config,
+    installation: &Installation,
+) -> Result<FfnxInstallation, Box<dyn std::error::Error>> {
+    let ffnx_dir = mumba_config.ffnx_dir(installation);
+    FfnxInstallation::from_directory(&ffnx_dir, installation)
+        .ok_or_else(|| "FFNx is not installed, please run \"mmb install\" first".into())
+}
+
+/// Prints install steps, progress is rewritten on the same line
+#[derive(Default)]
+struct ConsoleListener {
+    progress_line: Cell<bool>,
+}
+
+impl InstallListener for ConsoleListener {
+    fn on_event(&self, event: InstallEvent) {
+        if let InstallEvent::Progress(progress) = event {
+            print!("\r{:<60}", progress.to_string());
+            let _ = std::io::stdout().flush();
+            self.progress_line.set(true);
+            return;
+        }
+        if self.progress_line.replace(false) {
+            println!()
+        }
+        println!("{}", event)
+    }
+}
+
+fn search() -> CliResult {
+    let installations = Installation::search();
+    if installations.is_empty() {
+        println!("No game installation found, use \"mmb setup <EXE_PATH>\" instead");
+    }
+    for installation in installations {
+        println!(
+            "{:?} {} ({:?}): \"{}\"",
+            installation.edition,
+            installation.language,
+            installation.source,
+            installation.exe_path().to_string_lossy()
+        );
+    }
+    Ok(())
+}
+
+fn verify(env: &Env, profile: Option<&str>) -> CliResult {
+    let (mumba_config, installation) = retrieve_installation(env, profile)?;
+    let report = verify::verify(&installation, &mumba_config.ffnx_dir(&installation))?;
+    for issue in &report.issues {
+        println!("{}", issue)
+    }
+    if !report.is_ok() {
+        return Err(format!("{} problem(s) found", report.issues.len()).into());
+    }
+    println!("{} files checked, no problem found", report.checked_files);
+    Ok(())
+}
+
+fn setup(env: &Env, profile: Option<&str>, sub_matches: &clap::ArgMatches) -> CliResult {
+    let exe_path = sub_matches.get_one::<String>("EXE_PATH").expect("required");
+    let installation = Installation::from_exe_path(exe_path)?;
+    let mut mumba_config = open_mumba_config(env, profile);
+    mumba_config.set_installation(&installation);
+    if let Some(update_channel) = sub_matches.get_one::<String>("update-channel") {
+        mumba_config.set_update_channel(match update_channel.as_str() {
+            "beta" => UpdateChannel::Beta,
+            "alpha" => UpdateChannel::Alpha,
+            _ => UpdateChannel::Stable,
+        });
+    }
+    if let Some(language) = sub_matches.get_one::<String>("language") {
+        mumba_config.set_language(language);
+    }
+    if let Some(ffnx_path) = sub_matches.get_one::<String>("ffnx-path") {
+        mumba_config.set_data_path(&PathBuf::from(ffnx_path));
+    }
+    mumba_config.save(&env.config_path)?;
+    println!(
+        "Game {:?} {} found at \"{}\"",
+        installation.edition,
+        installation.language,
+        installation.app_path.to_string_lossy()
+    );
+    if !installation.edition.supports(Feature::Ffnx) {
+        println!("FFNx does not support this edition of the game, it cannot be installed");
+    }
+    Ok(())
+}
+
+fn ffnx_release(mumba_config: &Config, sub_matches: &clap::ArgMatches) -> FfnxRelease {
+    match sub_matches.get_one::<String>("from-zip") {
+        Some(zip_path) => FfnxRelease::LocalZip(PathBuf::from(zip_path)),
+        None => FfnxRelease::from_config(mumba_config),
+    }
+}
+
+fn install(env: &Env, profile: Option<&str>, sub_matches: &clap::ArgMatches) -> CliResult {
+    let (mut mumba_config, installation) = retrieve_installation(env, profile)?;
+    let ffnx_dir = mumba_config.ffnx_dir(&installation);
+    let release = ffnx_release(&mumba_config, sub_matches);
+    if matches!(release, FfnxRelease::LocalZip(_))
+        && FfnxInstallation::from_directory(&ffnx_dir, &installation).is_some()
+    {
+        return Err("FFNx is already installed, use \"mmb upgrade --from-zip\" instead".into());
+    }
+    let source = mumba_config.release_source()?;
+    let ffnx_installation = Installer::new(env, &ConsoleListener::default())
+        .with_source(source.as_ref())
+        .install(&ffnx_dir, &installation, &release)?;
+    mumba_config.set_data_path(&ffnx_dir);
+    mumba_config.save(&env.config_path)?;
+    println!("FFNx {} is installed", ffnx_installation.version);
+    Ok(())
+}
+
+fn upgrade(env: &Env, profile: Option<&str>, sub_matches: &clap::ArgMatches) -> CliResult {
+    let (mumba_config, installation) = retrieve_installation(env, profile)?;
+    let ffnx_installation = find_ffnx_installation(&mumba_config, &installation)?;
+    let release = ffnx_release(&mumba_config, sub_matches);
+    let source = mumba_config.release_source()?;
+    let listener = ConsoleListener::default();
+    let installer = Installer::new(env, &listener).with_source(source.as_ref());
+    if sub_matches.get_flag("dry-run") {
+        print_changelog(
+            &ffnx_installation,
+            &installer.changelog(&ffnx_installation, &release)?,
+        );
+        return Ok(());
+    }
+    if installer.upgrade(
+        &ffnx_installation,
+        &installation,
+        &release,
+        sub_matches.get_flag("force"),
+    )? {
+        print_ffnx_version(&ffnx_installation.path, &installation);
+    }
+    Ok(())
+}
+
+fn print_changelog(ffnx_installation: &FfnxInstallation, changelog: &Changelog) {
+    let releases = match changelog {
+        Changelog::UpToDate => {
+            println!("FFNx is up to date");
+            return;
+        }
+        Changelog::Upgrade(releases) => {
+            if releases.is_empty() {
+                println!("No release notes for a local archive");
+            }
+            releases.as_slice()
+        }
+        Changelog::Downgrade(release) => {
+            println!(
+                "FFNx {} would be downgraded to the pinned version {}\n",
+                ffnx_installation.version, release.tag_name
+            );
+            std::slice::from_ref(release)
+        }
+    };
+    for release in releases {
+        print_release_notes(release)
+    }
+}
+
+fn print_release_notes(release: &GitHubRelease) {
+    println!("== {} ({}) ==", release.tag_name, release_date(release));
+    if !release.html_url.is_empty() {
+        println!("{}", release.html_url)
+    }
+    if let Some(body) = &release.body {
+        println!("\n{}", markdown::to_plain_text(body))
+    }
+    println!()
+}
+
+/// The day of publication, drafts have none
+fn release_date(release: &GitHubRelease) -> &str {
+    release
+        .published_at
+        .as_deref()
+        .and_then(|published_at| published_at.get(..10))
+        .unwrap_or_default()
+}
+
+fn releases(env: &Env, profile: Option<&str>) -> CliResult {
+    let mumba_config = open_mumba_config(env, profile);
+    let pinned_version = mumba_config.ffnx_version()?;
+    for release in mumba_config.release_source()?.list_releases()? {
+        if release.draft {
+            continue;
+        }
+        println!(
+            "{:<20} {:<12} {}{}",
+            release.tag_name,
+            release_date(&release),
+            if release.prerelease { "prerelease" } else { "" },
+            if pinned_version.as_ref() == Some(&release.tag_name) {
+                " (pinned)"
+            } else {
+                ""
+            }
+        )
+    }
+    Ok(())
+}
+
+fn pin(env: &Env, profile: Option<&str>, sub_matches: &clap::ArgMatches) -> CliResult {
+    let tag = sub_matches.get_one::<String>("TAG").expect("required");
+    let mut mumba_config = open_mumba_config(env, profile);
+    let release = mumba_config
+        .release_source()?
+        .find_release_by_tag(tag)
+        .map_err(|e| format!("Cannot find the FFNx release {}: {}", tag, e))?
+        .ok_or_else(|| format!("There is no FFNx release {}", tag))?;
+    mumba_config.set_ffnx_version(Some(&release.tag_name));
+    mumba_config.save(&env.config_path)?;
+    println!(
+        "FFNx is pinned to {}, run \"mmb upgrade\" to install it",
+        release.tag_name
+    );
+    Ok(())
+}
+
+fn unpin(env: &Env, profile: Option<&str>) -> CliResult {
+    let mut mumba_config = open_mumba_config(env, profile);
+    mumba_config.set_ffnx_version(None);
+    mumba_config.save(&env.config_path)?;
+    Ok(())
+}
+
+fn rollback(env: &Env, profile: Option<&str>) -> CliResult {
+    let (installation, ffnx_installation) = retrieve_ffnx_installation(env, profile)?;
+    Installer::new(env, &ConsoleListener::default()).rollback(&ffnx_installation)?;
+    print_ffnx_version(&ffnx_installation.path, &installation);
+    Ok(())
+}
+
+fn uninstall(env: &Env, profile: Option<&str>) -> CliResult {
+    let (mut mumba_config, installation) = retrieve_installation(env, profile)?;
+    let ffnx_dir = mumba_config.ffnx_dir(&installation);
+    Installer::new(env, &ConsoleListener::default()).uninstall(&ffnx_dir)?;
+    mumba_config.clear_installation();
+    mumba_config.save(&env.config_path)?;
+    println!("FFNx uninstalled from \"{}\"", ffnx_dir.to_string_lossy());
+    Ok(())
+}
+
+fn self_update(env: &Env, profile: Option<&str>, sub_matches: &clap::ArgMatches) -> CliResult {
+    let mumba_config = open_mumba_config(env, profile);
+    let update = self_update::find_update(
+        mumba_config
+            .update_channel()
+            .unwrap_or(UpdateChannel::Stable),
+        &mumba_config.language().unwrap_or_default(),
+    )?;
+    let Some(update) = update else {
+        println!("Mumba {} is up to date", self_update::MUMBA_VERSION);
+        return Ok(());
+    };
+    println!(
+        "Mumba {} is available (installed: {})",
+        update.version,
+        self_update::MUMBA_VERSION
+    );
+    if sub_matches.get_flag("check") {
+        return Ok(());
+    }
+    let listener = ConsoleListener::default();
+    let progress = |progress| listener.on_event(InstallEvent::Progress(progress));
+    let result = self_update::install(&update, env, &progress)?;
+    listener.on_event(InstallEvent::Finished);
+    match result {
+        SelfUpdateResult::InstallerStarted => println!("The installer is running"),
+        SelfUpdateResult::Replaced => println!("Mumba {} is installed", update.version),
+        SelfUpdateResult::Downloaded(package_path) => println!(
+            "Mumba {} is downloaded, install it with:\nsudo apt install \"{}\"",
+            update.version,
+            package_path.to_string_lossy()
+        ),
+    }
+    Ok(())
+}
+
+fn print_ffnx_version(ffnx_dir: &Path, installation: &Installation) {
+    if let Some(ffnx_installation) = FfnxInstallation::from_directory(ffnx_dir, installation) {
+        println!("FFNx {} is installed", ffnx_installation.version)
+    }
+}
+
+fn launch(env: &Env, profile: Option<&str>) -> CliResult {
+    let (installation, ffnx_installation) = retrieve_ffnx_installation(env, profile)?;
+    let config_path = ffnx_installation.config_path();
+    let mut ffnx_config = FfnxConfig::from_file(&config_path).unwrap_or_default();
+    ffnx_config.set_app_path(&installation);
+    ffnx_config.save(&config_path)?;
+    Ok(ffnx_installation.launch_game(&installation)?)
+}
+
+fn launch_cw(env: &Env, profile: Option<&str>) -> CliResult {
+    let (_, installation) = retrieve_installation(env, profile)?;
+    Ok(installation.launch_cw()?)
 }
 
-fn main() -> std::io::Result<()> {
+fn cache(env: &Env, sub_matches: &clap::ArgMatches) -> CliResult {
+    const MB: f64 = 1_000_000.0;
+    let cache = DownloadCache::from_env(env);
+    match sub_matches.subcommand() {
+        Some(("list", _)) => {
+            let entries = cache.entries()?;
+            for entry in &entries {
+                let days = std::time::SystemTime::now()
+                    .duration_since(entry.last_used)
+                    .unwrap_or_default()
+                    .as_secs()
+                    / 86400;
+                println!(
+                    "{:<60} {:>8.1} MB  used {} days ago",
+                    entry.file_name(),
+                    entry.size as f64 / MB,
+                    days
+                )
+            }
+            println!(
+                "{} archives, {:.1} MB in \"{}\"",
+                entries.len(),
+                entries.iter().map(|entry| entry.size).sum::<u64>() as f64 / MB,
+                cache.dir().to_string_lossy()
+            )
+        }
+        Some(("clear", _)) => {
+            let freed_size = cache.clear()?;
+            println!("{:.1} MB freed", freed_size as f64 / MB)
+        }
+        Some((_, _)) | None => unreachable!(),
+    }
+    Ok(())
+}
+
+fn journal(env: &Env, sub_matches: &clap::ArgMatches) -> CliResult {
+    let mut journal = InstallJournal::from_env(env)?;
+    if *journal.state() == State::Empty {
+        println!("No install recorded");
+        return Ok(());
+    }
+    match sub_matches.subcommand() {
+        Some(("show", _)) => {
+            println!(
+                "Install in \"{}\" started at {} ({:?})",
+                journal.target_dir().to_string_lossy(),
+                journal.started_at(),
+                journal.state()
+            );
+            for step in journal.steps() {
+                println!("{}", step)
+            }
+        }
+        Some(("undo", _)) => {
+            if *journal.state() != State::InProgress {
+                return Err(format!(
+                    "The last install is {:?}, only an interrupted install can be undone",
+                    journal.state()
+                )
+                .into());
+            }
+            journal.undo()?;
+            println!("{} steps undone", journal.steps().len())
+        }
+        Some((_, _)) | None => unreachable!(),
+    }
+    Ok(())
+}
+
+fn profile(env: &Env, profile: Option<&str>, sub_matches: &clap::ArgMatches) -> CliResult {
+    let mut mumba_config = open_mumba_config(env, profile);
+    match sub_matches.subcommand() {
+        Some(("list", _)) => {
+            let current_profile = String::from(mumba_config.profile());
+            for name in mumba_config.profiles() {
+                mumba_config.use_profile(&name);
+                let exe_path = match mumba_config.installation() {
+                    Ok(Some(installation)) => {
+                        installation.exe_path().to_string_lossy().into_owned()
+                    }
+                    Ok(None) => String::from("(no game configured)"),
+                    Err(e) => format!("(invalid configuration: {})", e),
+                };
+                let marker = if name == current_profile { "*" } else { " " };
+                println!("{} {}\t{}", marker, name, exe_path)
+            }
+        }
+        Some(("use", sub_matches)) => {
+            let name = sub_matches.get_one::<String>("NAME").expect("required");
+            mumba_config.set_current_profile(name);
+            mumba_config.save(&env.config_path)?;
+            if mumba_config.installation()?.is_none() {
+                println!(
+                    "The profile {} has no game, please run \"mmb setup <EXE_PATH>\"",
+                    name
+                )
+            }
+        }
+        Some(("remove", sub_matches)) => {
+            let name = sub_matches.get_one::<String>("NAME").expect("required");
+            if !mumba_config.remove_profile(name) {
+                return Err(format!("The profile {} cannot be removed", name).into());
+            }
+            mumba_config.save(&env.config_path)?
+        }
+        Some((_, _)) | None => unreachable!(),
+    }
+    Ok(())
+}
+
+fn config(env: &Env, profile: Option<&str>, sub_matches: &clap::ArgMatches) -> CliResult {
+    let (_, ffnx_installation) = retrieve_ffnx_installation(env, profile)?;
+    let config_path = ffnx_installation.config_path();
+    let mut ffnx_config = FfnxConfig::from_file(&config_path).unwrap_or_default();
+    match sub_matches.subcommand() {
+        Some(("get", sub_matches)) => {
+            let key = sub_matches.get_one::<String>("KEY").expect("required");
+            match ffnx_config.root().get(key) {
+                Some(value) => println!("{}", value.to_string().trim()),
+                None => return Err(format!("The key {} is absent", key).into()),
+            }
+        }
+        Some(("set", sub_matches)) => {
+            let key = sub_matches.get_one::<String>("KEY").expect("required");
+            let value = sub_matches.get_one::<String>("VALUE").expect("required");
+            if let Ok(value) = value.parse::<bool>() {
+                ffnx_config.set_bool(key, value)
+            } else if let Ok(value) = value.parse::<i64>() {
+                ffnx_config.set_int(key, value)
+            } else {
+                ffnx_config.set_string(key, value)
+            }
+            ffnx_config.save(&config_path)?
+        }
+        Some((_, _)) | None => unreachable!(),
+    }
+    Ok(())
+}
+
+fn main() -> CliResult {
     let env = Env::new("mmb")?;
     mumba_core::mumba_log::init(&env.log_path);
-
+    self_update::remove_backup();
     let matches = cli().get_matches();
+    let selected_profile = matches.get_one::<String>("profile").map(String::as_str);
+    if let Err(e) = open_mumba_config(&env, selected_profile)
+        .http_config()
+        .map_err(|e| e.to_string())
+        .and_then(|http_config| http::init(&http_config).map_err(|e| e.to_string()))
+    {
+        eprintln!("Invalid [http] configuration in mumba.toml: {}", e)
+    }
 
     match matches.subcommand() {
         Some(("replace_launcher", sub_matches)) => {
             let app_path = sub_matches.get_one::<String>("APP_PATH").expect("required");
             match Installation::from_directory(app_path, Edition::Steam) {
-                Some(installation) => installation.replace_launcher_from_app_path(&env),
-                None => Err(std::io::Error::new(
+                Some(installation) => Ok(installation.replace_launcher_from_app_path(
+                    &env,
+                    &mut |source, target| {
+                        provision::copy_file(&source.to_path_buf(), &target.to_path_buf())
+                    },
+                )?),
+                None => Err(Box::new(std::io::Error::new(
                     std::io::ErrorKind::NotFound,
                     "App not found",
-                )),
+                ))),
             }
         }
+        Some(("search", _)) => search(),
+        Some(("verify", _)) => verify(&env, selected_profile),
+        Some(("setup", sub_matches)) => setup(&env, selected_profile, sub_matches),
+        Some(("install", sub_matches)) => install(&env, selected_profile, sub_matches),
+        Some(("upgrade", sub_matches)) => upgrade(&env, selected_profile, sub_matches),
+        Some(("releases", _)) => releases(&env, selected_profile),
+        Some(("pin", sub_matches)) => pin(&env, selected_profile, sub_matches),
+        Some(("unpin", _)) => unpin(&env, selected_profile),
+        Some(("rollback", _)) => rollback(&env, selected_profile),
+        Some(("uninstall", _)) => uninstall(&env, selected_profile),
+        Some(("self-update", sub_matches)) => self_update(&env, selected_profile, sub_matches),
+        Some(("launch", _)) => launch(&env, selected_profile),
+        Some(("launch-cw", _)) => launch_cw(&env, selected_profile),
+        Some(("cache", sub_matches)) => cache(&env, sub_matches),
+        Some(("journal", sub_matches)) => journal(&env, sub_matches),
+        Some(("profile", sub_matches)) => profile(&env, selected_profile, sub_matches),
+        Some(("config", sub_matches)) => config(&env, selected_profile, sub_matches),
         Some((_, _)) | None => unreachable!(),
     }
 }
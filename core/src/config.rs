@@ -1,6 +1,12 @@
 use crate::game::installation::Installation;
+#[cfg(feature = "network")]
+use crate::http::HttpConfig;
+#[cfg(feature = "network")]
+use crate::release_source::{GitHubSource, LocalSource, ManifestSource, ReleaseSource};
 use crate::toml;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "network")]
+use std::time::Duration;
 use thiserror::Error;
 use toml_edit::DocumentMut;
 
@@ -8,6 +14,24 @@ const CFG_EXE_PATH: &str = "exe_path";
 const CFG_DATA_PATH: &str = "data_path";
 const CFG_UPDATE_CHANNEL: &str = "update_channel";
 const CFG_LANGUAGE: &str = "language";
+const CFG_FFNX_VERSION: &str = "ffnx_version";
+const CFG_PROFILE: &str = "profile";
+const CFG_PROFILES: &str = "profiles";
+/// The profile stored at the root of mumba.toml, other profiles are in `[profiles.<name>]` tables
+pub const DEFAULT_PROFILE: &str = "default";
+#[cfg(feature = "network")]
+const CFG_HTTP: &str = "http";
+#[cfg(feature = "network")]
+const CFG_RELEASE_SOURCE: &str = "release_source";
+#[cfg(feature = "network")]
+const CFG_CHECK_UPDATE_ON_STARTUP: &str = "check_update_on_startup";
+#[cfg(feature = "network")]
+const CFG_LAST_UPDATE_CHECK: &str = "last_update_check";
+#[cfg(feature = "network")]
+const CFG_FFNX_UPDATE: &str = "ffnx_update";
+/// Updates are checked on startup at most once per day
+#[cfg(feature = "network")]
+const UPDATE_CHECK_INTERVAL: jiff::SignedDuration = jiff::SignedDuration::from_hours(24);
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -24,21 +48,29 @@ pub enum UpdateChannel {
     Alpha = 2,
 }
 
+/// mumba.toml, the game settings (`exe_path`, `data_path`, `update_channel` and `ffnx_version`)
+/// are read from and written to the selected profile
 pub struct Config {
     inner: DocumentMut,
+    profile: Option<String>,
 }
 
 impl Config {
     pub fn new() -> Self {
         Self {
             inner: DocumentMut::new(),
+            profile: None,
         }
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, toml::FileError> {
-        Ok(Self {
+        let mut config = Self {
             inner: toml::parse_from_file(path)?,
-        })
+            profile: None,
+        };
+        let profile = String::from(toml::get_string(config.root(), CFG_PROFILE, "").unwrap_or(""));
+        config.use_profile(&profile);
+        Ok(config)
     }
 
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), toml::FileError> {
@@ -47,7 +79,10 @@ impl Config {
 
     pub fn installation(&self) -> Result<Option<Installation>, toml::Error> {
         let key = CFG_EXE_PATH;
-        let exe_path = toml::get_string(self.root(), key, "")?;
+        let Some(profile) = self.profile_table() else {
+            return Ok(None);
+        };
+        let exe_path = toml::get_string(profile, key, "")?;
         if exe_path.is_empty() {
             return Ok(None);
         }
@@ -58,13 +93,23 @@ impl Config {
 
     pub fn set_installation(&mut self, installation: &Installation) {
         if let Some(exe_path) = installation.exe_path().to_str() {
-            self.inner[CFG_EXE_PATH] = toml_edit::Item::Value(exe_path.into())
+            self.profile_table_mut()[CFG_EXE_PATH] = toml_edit::Item::Value(exe_path.into())
         }
     }
 
+    /// Forgets the game of the profile and its FFNx directory, after an uninstall
+    pub fn clear_installation(&mut self) {
+        let profile = self.profile_table_mut();
+        profile.remove(CFG_EXE_PATH);
+        profile.remove(CFG_DATA_PATH);
+    }
+
     pub fn data_path(&self) -> Result<Option<String>, toml::Error> {
         let key = CFG_DATA_PATH;
-        let data_path = toml::get_string(self.root(), key, "")?;
+        let Some(profile) = self.profile_table() else {
+            return Ok(None);
+        };
+        let data_path = toml::get_string(profile, key, "")?;
         if data_path.is_empty() {
             Ok(None)
         } else {
@@ -73,23 +118,187 @@ impl Config {
     }
 
     pub fn set_data_path(&mut self, data_path: &Path) {
-        self.inner[CFG_DATA_PATH] =
+        self.profile_table_mut()[CFG_DATA_PATH] =
             toml_edit::Item::Value(data_path.to_string_lossy().into_owned().into())
     }
 
+    pub fn ffnx_dir(&self, installation: &Installation) -> PathBuf {
+        match self.data_path() {
+            Ok(Some(ffnx_path)) => PathBuf::from(ffnx_path),
+            Ok(None) | Err(_) => installation.app_path.join("mumba"),
+        }
+    }
+
     pub fn update_channel(&self) -> Result<UpdateChannel, toml::Error> {
-        Ok(
-            match toml::get_integer(self.root(), CFG_UPDATE_CHANNEL, 0)? {
-                0 => UpdateChannel::Stable,
-                1 => UpdateChannel::Beta,
-                2 => UpdateChannel::Alpha,
-                _ => UpdateChannel::Stable,
-            },
-        )
+        let Some(profile) = self.profile_table() else {
+            return Ok(UpdateChannel::Stable);
+        };
+        Ok(match toml::get_integer(profile, CFG_UPDATE_CHANNEL, 0)? {
+            0 => UpdateChannel::Stable,
+            1 => UpdateChannel::Beta,
+            2 => UpdateChannel::Alpha,
+            _ => UpdateChannel::Stable,
+        })
     }
 
     pub fn set_update_channel(&mut self, update_channel: UpdateChannel) {
-        self.inner[CFG_UPDATE_CHANNEL] = toml_edit::Item::Value((update_channel as i64).into())
+        self.profile_table_mut()[CFG_UPDATE_CHANNEL] =
+            toml_edit::Item::Value((update_channel as i64).into())
+    }
+
+    /// The FFNx release tag to install instead of following the update channel
+    pub fn ffnx_version(&self) -> Result<Option<String>, toml::Error> {
+        let Some(profile) = self.profile_table() else {
+            return Ok(None);
+        };
+        let ffnx_version = toml::get_string(profile, CFG_FFNX_VERSION, "")?;
+        if ffnx_version.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(String::from(ffnx_version)))
+        }
+    }
+
+    pub fn set_ffnx_version(&mut self, ffnx_version: Option<&str>) {
+        match ffnx_version {
+            Some(ffnx_version) => {
+                self.profile_table_mut()[CFG_FFNX_VERSION] =
+                    toml_edit::Item::Value(ffnx_version.into())
+            }
+            None => {
+                self.profile_table_mut().remove(CFG_FFNX_VERSION);
+            }
+        }
+    }
+
+    #[cfg(feature = "network")]
+    pub fn http_config(&self) -> Result<HttpConfig, toml::Error> {
+        let table = match self.root().get(CFG_HTTP) {
+            Some(item) => item.as_table().ok_or_else(|| {
+                toml::Error::WrongTypeError(String::from(CFG_HTTP), String::from("Table"))
+            })?,
+            None => return Ok(HttpConfig::default()),
+        };
+        let string = |key: &str| -> Result<Option<String>, toml::Error> {
+            let value = toml::get_string(table, key, "")?;
+            Ok(Some(String::from(value)).filter(|value| !value.is_empty()))
+        };
+        let seconds = |key: &str| -> Result<Option<Duration>, toml::Error> {
+            let value = toml::get_integer(table, key, 0)?;
+            Ok(Some(Duration::from_secs(value as u64)).filter(|_| value > 0))
+        };
+        Ok(HttpConfig {
+            proxy: string("proxy")?,
+            connect_timeout: seconds("connect_timeout")?,
+            timeout: seconds("timeout")?,
+            user_agent: string("user_agent")?,
+            github_token: string("github_token")?,
+        })
+    }
+
+    #[cfg(feature = "network")]
+    pub fn check_update_on_startup(&self) -> Result<bool, toml::Error> {
+        toml::get_boolean(self.root(), CFG_CHECK_UPDATE_ON_STARTUP, true)
+    }
+
+    #[cfg(feature = "network")]
+    pub fn set_check_update_on_startup(&mut self, check: bool) {
+        self.inner[CFG_CHECK_UPDATE_ON_STARTUP] = toml_edit::Item::Value(check.into())
+    }
+
+    /// The last FFNx update check of the profile
+    #[cfg(feature = "network")]
+    pub fn last_update_check(&self) -> Result<Option<jiff::Timestamp>, toml::Error> {
+        let Some(profile) = self.profile_table() else {
+            return Ok(None);
+        };
+        match toml::get_string(profile, CFG_LAST_UPDATE_CHECK, "")? {
+            "" => Ok(None),
+            last_check => last_check.parse().map(Some).map_err(|_| {
+                toml::Error::WrongTypeError(
+                    String::from(CFG_LAST_UPDATE_CHECK),
+                    String::from("Timestamp"),
+                )
+            }),
+        }
+    }
+
+    #[cfg(feature = "network")]
+    pub fn set_update_checked(&mut self) {
+        self.profile_table_mut()[CFG_LAST_UPDATE_CHECK] =
+            toml_edit::Item::Value(jiff::Timestamp::now().to_string().into())
+    }
+
+    /// The tag of the FFNx release found by the last update check of the profile
+    #[cfg(feature = "network")]
+    pub fn ffnx_update(&self) -> Result<Option<String>, toml::Error> {
+        let Some(profile) = self.profile_table() else {
+            return Ok(None);
+        };
+        let ffnx_update = toml::get_string(profile, CFG_FFNX_UPDATE, "")?;
+        Ok(Some(String::from(ffnx_update)).filter(|tag| !tag.is_empty()))
+    }
+
+    #[cfg(feature = "network")]
+    pub fn set_ffnx_update(&mut self, tag: Option<&str>) {
+        match tag {
+            Some(tag) => {
+                self.profile_table_mut()[CFG_FFNX_UPDATE] = toml_edit::Item::Value(tag.into())
+            }
+            None => {
+                self.profile_table_mut().remove(CFG_FFNX_UPDATE);
+            }
+        }
+    }
+
+    /// Whether the startup check is enabled and the last one is older than a day
+    #[cfg(feature = "network")]
+    pub fn is_update_check_due(&self) -> bool {
+        if !self.check_update_on_startup().unwrap_or(true) {
+            return false;
+        }
+        match self.last_update_check() {
+            Ok(Some(last_check)) => {
+                let elapsed = jiff::Timestamp::now().duration_since(last_check);
+                // A negative duration means the clock went back
+                elapsed >= UPDATE_CHECK_INTERVAL || elapsed.is_negative()
+            }
+            _ => true,
+        }
+    }
+
+    /// Where to look for FFNx releases, GitHub unless the `[release_source]` table says otherwise
+    #[cfg(feature = "network")]
+    pub fn release_source(&self) -> Result<Box<dyn ReleaseSource>, toml::Error> {
+        let table = match self.root().get(CFG_RELEASE_SOURCE) {
+            Some(item) => item.as_table().ok_or_else(|| {
+                toml::Error::WrongTypeError(String::from(CFG_RELEASE_SOURCE), String::from("Table"))
+            })?,
+            None => return Ok(Box::new(GitHubSource::default())),
+        };
+        let required = |key: &str| -> Result<String, toml::Error> {
+            match toml::get_string(table, key, "")? {
+                "" => Err(toml::Error::DoesNotExist(format!(
+                    "{}.{}",
+                    CFG_RELEASE_SOURCE, key
+                ))),
+                value => Ok(String::from(value)),
+            }
+        };
+        Ok(match toml::get_string(table, "type", "github")? {
+            "github" => match toml::get_string(table, "repo", "")? {
+                "" => Box::new(GitHubSource::default()),
+                repo => Box::new(GitHubSource::new(repo)),
+            },
+            "manifest" => Box::new(ManifestSource::new(required("url")?)),
+            "local" => Box::new(LocalSource::new(required("path")?)),
+            _ => {
+                return Err(toml::Error::WrongTypeError(
+                    format!("{}.type", CFG_RELEASE_SOURCE),
+                    String::from("\"github\", \"manifest\" or \"local\""),
+                ))
+            }
+        })
     }
 
     pub fn language(&self) -> Result<String, toml::Error> {
@@ -104,6 +313,99 @@ impl Config {
         self.inner[CFG_LANGUAGE] = toml_edit::Item::Value(lang.into())
     }
 
+    /// The selected profile, `DEFAULT_PROFILE` unless `use_profile` says otherwise
+    pub fn profile(&self) -> &str {
+        self.profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Selects the profile used by this instance, it is created on the first change
+    pub fn use_profile(&mut self, name: &str) {
+        self.profile =
+            Some(String::from(name)).filter(|name| !name.is_empty() && name != DEFAULT_PROFILE);
+    }
+
+    /// Selects the profile and uses it on the next start
+    pub fn set_current_profile(&mut self, name: &str) {
+        self.use_profile(name);
+        match &self.profile {
+            Some(name) => self.inner[CFG_PROFILE] = toml_edit::Item::Value(name.into()),
+            None => {
+                self.inner.remove(CFG_PROFILE);
+            }
+        }
+    }
+
+    /// The default profile first, then the `[profiles.<name>]` tables
+    pub fn profiles(&self) -> Vec<String> {
+        let mut profiles = vec![String::from(DEFAULT_PROFILE)];
+        if let Some(tables) = self
+            .root()
+            .get(CFG_PROFILES)
+            .and_then(|item| item.as_table())
+        {
+            profiles.extend(
+                tables
+                    .iter()
+                    .filter(|(_, item)| item.is_table())
+                    .map(|(name, _)| String::from(name)),
+            )
+        }
+        if let Some(profile) = &self.profile {
+            if !profiles.contains(profile) {
+                profiles.push(profile.clone())
+            }
+        }
+        profiles
+    }
+
+    /// The default profile cannot be removed, the default profile is selected
+    /// when the selected one is removed
+    pub fn remove_profile(&mut self, name: &str) -> bool {
+        let removed = self
+            .inner
+            .get_mut(CFG_PROFILES)
+            .and_then(|item| item.as_table_mut())
+            .and_then(|tables| tables.remove(name))
+            .is_some();
+        if removed && self.profile.as_deref() == Some(name) {
+            self.set_current_profile(DEFAULT_PROFILE)
+        }
+        removed
+    }
+
+    fn profile_table(&self) -> Option<&toml_edit::Table> {
+        match &self.profile {
+            Some(name) => self
+                .root()
+                .get(CFG_PROFILES)?
+                .as_table()?
+                .get(name)?
+                .as_table(),
+            None => Some(self.root()),
+        }
+    }
+
+    fn profile_table_mut(&mut self) -> &mut toml_edit::Table {
+        let Some(name) = self.profile.clone() else {
+            return self.inner.as_table_mut();
+        };
+        let tables = self
+            .inner
+            .entry(CFG_PROFILES)
+            .or_insert_with(toml_edit::table);
+        if !tables.is_table() {
+            *tables = toml_edit::table()
+        }
+        let tables = tables.as_table_mut().expect("profiles is a table");
+        // Only the [profiles.<name>] headers are written
+        tables.set_implicit(true);
+        let table = tables.entry(&name).or_insert_with(toml_edit::table);
+        if !table.is_table() {
+            *table = toml_edit::table()
+        }
+        table.as_table_mut().expect("the profile is a table")
+    }
+
     fn root(&self) -> &toml_edit::Table {
         self.inner.as_table()
     }
@@ -0,0 +1,140 @@
+use crate::game::env::Env;
+use crate::provision::{self, RemoteFile};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Archives are evicted, least recently used first, when the cache grows beyond this size
+pub const DEFAULT_MAX_SIZE: u64 = 1_000_000_000;
+
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+impl CacheEntry {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+/// Keeps downloaded archives, keyed by URL, to avoid downloading them again
+pub struct DownloadCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl DownloadCache {
+    pub fn new(dir: PathBuf, max_size: u64) -> Self {
+        Self { dir, max_size }
+    }
+
+    pub fn from_env(env: &Env) -> Self {
+        Self::new(env.cache_dir.join("downloads"), DEFAULT_MAX_SIZE)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path of the archive in the cache, the URL hash prevents collisions between tags
+    pub fn path_for(&self, url: &str) -> PathBuf {
+        let hash: String = Sha256::digest(url.as_bytes())
+            .iter()
+            .take(8)
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let name: String = url
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{}-{}", hash, name))
+    }
+
+    /// Returns the cached archive if it is still valid, and marks it as recently used
+    pub fn get(&self, remote: &RemoteFile) -> Option<PathBuf> {
+        let path = self.path_for(&remote.url);
+        let size = std::fs::metadata(&path).ok()?.len();
+        if let Err(e) = provision::verify_file(&path, size, remote) {
+            warn!(
+                "Remove invalid file \"{}\" from the cache: {}",
+                path.to_string_lossy(),
+                e
+            );
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        if let Err(e) = std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            warn!(
+                "Cannot update the date of \"{}\": {}",
+                path.to_string_lossy(),
+                e
+            )
+        }
+        info!("Use cached file \"{}\"", path.to_string_lossy());
+        Some(path)
+    }
+
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, std::io::Error> {
+        let read_dir = match std::fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut entries = Vec::new();
+        for entry in read_dir {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                entries.push(CacheEntry {
+                    path: entry.path(),
+                    size: metadata.len(),
+                    last_used: metadata.modified()?,
+                })
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_used));
+        Ok(entries)
+    }
+
+    /// Removes the least recently used archives until the cache fits in its maximum size
+    pub fn evict(&self) -> Result<(), std::io::Error> {
+        let mut total_size = 0;
+        for entry in self.entries()? {
+            total_size += entry.size;
+            if total_size > self.max_size {
+                info!("Evict \"{}\" from the cache", entry.path.to_string_lossy());
+                std::fs::remove_file(&entry.path)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes every archive, and returns the freed size
+    pub fn clear(&self) -> Result<u64, std::io::Error> {
+        let mut freed_size = 0;
+        for entry in self.entries()? {
+            std::fs::remove_file(&entry.path)?;
+            freed_size += entry.size
+        }
+        Ok(freed_size)
+    }
+}
@@ -0,0 +1,247 @@
+use crate::game::installation::{Installation, InstallationSource};
+#[cfg(windows)]
+use crate::os::regedit;
+#[cfg(feature = "discovery")]
+use serde::Deserialize;
+#[cfg(feature = "discovery")]
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The registry key written by the 2000 release installer
+const STANDARD_EDITION_REG_KEY: &str = r"square soft, inc\\final fantasy viii\\1.00";
+
+#[cfg(feature = "discovery")]
+#[derive(Deserialize, Debug)]
+struct HeroicGame {
+    install_path: PathBuf,
+}
+
+/// `gog_store/installed.json` has a list, Legendary's `installed.json` a map by app name
+#[cfg(feature = "discovery")]
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum HeroicInstalled {
+    Gog { installed: Vec<HeroicGame> },
+    Legendary(HashMap<String, HeroicGame>),
+}
+
+#[cfg(feature = "discovery")]
+#[derive(Deserialize, Debug, Default)]
+struct LutrisConfig {
+    #[serde(default)]
+    game: LutrisGame,
+}
+
+#[cfg(feature = "discovery")]
+#[derive(Deserialize, Debug, Default)]
+struct LutrisGame {
+    exe: Option<PathBuf>,
+    prefix: Option<PathBuf>,
+}
+
+/// Searches the game in the libraries of GOG Galaxy, Heroic, Lutris, Bottles and Wine prefixes
+pub fn search() -> Vec<Installation> {
+    let mut installations = Vec::new();
+    #[cfg(windows)]
+    installations.extend(search_gog_galaxy());
+    #[cfg(unix)]
+    if let Some(home) = std::env::var_os("HOME") {
+        installations.extend(search_in_home(Path::new(&home)))
+    }
+    installations
+}
+
+/// Launchers installed natively or with Flatpak, relative to the home directory
+pub fn search_in_home(home: &Path) -> Vec<Installation> {
+    let mut installations = Vec::new();
+    #[cfg(feature = "discovery")]
+    for config_dir in [
+        home.join(".config"),
+        home.join(".var/app/com.heroicgameslauncher.hgl/config"),
+    ] {
+        installations.extend(search_heroic(&config_dir.join("heroic")))
+    }
+    #[cfg(feature = "discovery")]
+    for lutris_dir in [
+        home.join(".local/share/lutris"),
+        home.join(".config/lutris"),
+        home.join(".var/app/net.lutris.Lutris/data/lutris"),
+    ] {
+        installations.extend(search_lutris(&lutris_dir.join("games")))
+    }
+    for bottles_dir in [
+        home.join(".local/share/bottles"),
+        home.join(".var/app/com.usebottles.bottles/data/bottles"),
+    ] {
+        installations.extend(search_bottles(&bottles_dir.join("bottles")))
+    }
+    installations.extend(search_wine_prefix(
+        &home.join(".wine"),
+        InstallationSource::Wine,
+    ));
+    installations
+}
+
+/// GOG and Epic games installed by Heroic, listed in its JSON files
+#[cfg(feature = "discovery")]
+pub fn search_heroic(heroic_dir: &Path) -> Vec<Installation> {
+    [
+        heroic_dir.join("gog_store").join("installed.json"),
+        heroic_dir
+            .join("legendaryConfig")
+            .join("legendary")
+            .join("installed.json"),
+    ]
+    .iter()
+    .filter_map(|path| {
+        let contents = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(installed) => Some(installed),
+            Err(e) => {
+                warn!("Cannot parse \"{}\": {}", path.to_string_lossy(), e);
+                None
+            }
+        }
+    })
+    .flat_map(|installed| match installed {
+        HeroicInstalled::Gog { installed } => installed,
+        HeroicInstalled::Legendary(installed) => installed.into_values().collect(),
+    })
+    .filter_map(|game| {
+        Installation::find_in_directory(&game.install_path, InstallationSource::Heroic)
+    })
+    .collect()
+}
+
+/// Games configured in Lutris, the executable or the Wine prefix of every game is checked
+#[cfg(feature = "discovery")]
+pub fn search_lutris(games_dir: &Path) -> Vec<Installation> {
+    let mut installations = Vec::new();
+    for path in list_dir(games_dir) {
+        if path.extension().is_none_or(|ext| ext != "yml") {
+            continue;
+        }
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let config: LutrisConfig = match serde_yaml_ng::from_str(&contents) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Cannot parse \"{}\": {}", path.to_string_lossy(), e);
+                continue;
+            }
+        };
+        if let Some(exe_path) = config.game.exe {
+            if let Ok(installation) = Installation::from_exe_path(&exe_path) {
+                installations.push(installation.with_source(InstallationSource::Lutris));
+                continue;
+            }
+        }
+        if let Some(prefix) = config.game.prefix {
+            installations.extend(search_wine_prefix(&prefix, InstallationSource::Lutris))
+        }
+    }
+    installations
+}
+
+/// Every bottle is a Wine prefix
+pub fn search_bottles(bottles_dir: &Path) -> Vec<Installation> {
+    list_dir(bottles_dir)
+        .iter()
+        .flat_map(|prefix| search_wine_prefix(prefix, InstallationSource::Bottles))
+        .collect()
+}
+
+/// Looks for the registry key of the 2000 release and the default Steam library of a Wine prefix
+pub fn search_wine_prefix(prefix: &Path, source: InstallationSource) -> Vec<Installation> {
+    let mut app_paths = Vec::new();
+    if let Ok(system_reg) = std::fs::read_to_string(prefix.join("system.reg")) {
+        app_paths.extend(
+            reg_file_value(&system_reg, STANDARD_EDITION_REG_KEY, "AppPath")
+                .and_then(|app_path| wine_path(prefix, &app_path)),
+        )
+    }
+    for program_files in ["Program Files (x86)", "Program Files"] {
+        let common_dir = prefix
+            .join("drive_c")
+            .join(program_files)
+            .join("Steam")
+            .join("steamapps")
+            .join("common");
+        app_paths.push(common_dir.join("FINAL FANTASY VIII"));
+        app_paths.push(common_dir.join("FINAL FANTASY VIII Remastered"))
+    }
+    app_paths
+        .iter()
+        .filter_map(|app_path| Installation::find_in_directory(app_path, source))
+        .collect()
+}
+
+/// Games installed by GOG Galaxy are listed in the registry
+#[cfg(windows)]
+pub fn search_gog_galaxy() -> Vec<Installation> {
+    search_gog_galaxy_key(
+        regedit::RegTarget::Wow32,
+        regedit::RegLocation::Machine,
+        r"SOFTWARE\GOG.com\Games",
+    )
+}
+
+/// One subkey per game, with the install directory in its `path` value
+#[cfg(windows)]
+pub fn search_gog_galaxy_key(
+    target: regedit::RegTarget,
+    loc: regedit::RegLocation,
+    games_key: &str,
+) -> Vec<Installation> {
+    regedit::reg_subkeys(target, loc, games_key)
+        .iter()
+        .filter_map(|game_id| {
+            regedit::reg_value_str(
+                target,
+                loc,
+                format!(r"{}\{}", games_key, game_id).as_str(),
+                "path",
+            )
+            .ok()
+        })
+        .filter_map(|app_path| Installation::find_in_directory(app_path, InstallationSource::Gog))
+        .collect()
+}
+
+fn list_dir(dir: &Path) -> Vec<PathBuf> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// A string value of a Wine registry file (system.reg), section names are case insensitive
+pub fn reg_file_value(contents: &str, key: &str, value_name: &str) -> Option<String> {
+    let value_prefix = format!("\"{}\"=\"", value_name);
+    let mut in_section = false;
+    for line in contents.lines() {
+        if let Some(section) = line.strip_prefix('[') {
+            let name = section.split(']').next().unwrap_or_default();
+            in_section = name.to_ascii_lowercase().ends_with(key);
+        } else if in_section {
+            if let Some(value) = line.strip_prefix(&value_prefix) {
+                return Some(value.trim_end().trim_end_matches('"').replace("\\\\", "\\"));
+            }
+        }
+    }
+    None
+}
+
+/// Converts a Windows path of a Wine prefix to a host path
+pub fn wine_path(prefix: &Path, windows_path: &str) -> Option<PathBuf> {
+    let (drive, path) = windows_path.split_once(':')?;
+    let drive = drive.to_ascii_lowercase();
+    let mut host_path = if drive == "c" {
+        prefix.join("drive_c")
+    } else {
+        prefix.join("dosdevices").join(format!("{}:", drive))
+    };
+    host_path.extend(path.split('\\').filter(|part| !part.is_empty()));
+    Some(host_path)
+}
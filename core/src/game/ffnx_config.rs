@@ -1,3 +1,4 @@
+use crate::game::installation::Installation;
 use crate::toml;
 use std::path::Path;
 use toml_edit::DocumentMut;
@@ -65,6 +66,17 @@ impl FfnxConfig {
         self.inner[key] = toml_edit::value(value.into())
     }
 
+    pub fn set_app_path(&mut self, installation: &Installation) {
+        self.set_string(
+            CFG_APP_PATH,
+            if cfg!(unix) {
+                String::from("..")
+            } else {
+                installation.app_path.to_string_lossy().to_string()
+            },
+        )
+    }
+
     pub fn get_string<'a>(&'a self, key: &str, default: &'a str) -> Result<&'a str, toml::Error> {
         toml::get_string(self.root(), key, default)
     }
@@ -4,25 +4,52 @@ use crate::config::UpdateChannel;
 use crate::game::env::Env;
 use crate::game::installation::{Edition, Installation};
 #[cfg(feature = "network")]
-use crate::github::GitHubReleaseAsset;
+use crate::github::{GitHubRelease, GitHubReleaseAsset, LatestRelease};
 #[cfg(feature = "pe")]
 use crate::pe_format;
 #[cfg(any(feature = "network", feature = "zip"))]
 use crate::provision;
+#[cfg(feature = "network")]
+use crate::provision::RemoteFile;
+#[cfg(feature = "network")]
+use crate::release_source::ReleaseSource;
+use crate::version::Version;
+#[cfg(any(feature = "zip", feature = "pe"))]
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Child;
 
+pub const FFNX_REPO_NAME: &str = "julianxhokaxhiu/FFNx";
+/// Suffix of the directory next to the FFNx one where a new version is extracted,
+/// before it is renamed in place
+pub const STAGING_DIR: &str = ".mumba-staging";
+/// Suffix of the directory next to the FFNx one where the last upgrade keeps the replaced files
+pub const BACKUP_DIR: &str = ".mumba-backup";
+
 pub struct FfnxInstallation {
-    pub version: String,
+    pub version: Version,
     pub path: PathBuf,
     pub exe_name: String,
 }
 
 impl FfnxInstallation {
     #[cfg(all(feature = "network", feature = "zip"))]
-    pub fn download(url: &str, target_dir: &PathBuf, env: &Env) -> Result<(), provision::Error> {
-        provision::download_zip(url, "FFNx.zip", target_dir, env)
+    pub fn download(
+        remote: &RemoteFile,
+        target_dir: &Path,
+        env: &Env,
+        progress: &dyn Fn(provision::Progress),
+    ) -> Result<(), provision::Error> {
+        provision::download_zip(remote, target_dir, env, progress)
+    }
+
+    #[cfg(feature = "zip")]
+    pub fn extract(
+        source_file: &Path,
+        target_dir: &Path,
+        progress: &dyn Fn(provision::Progress),
+    ) -> Result<(), zip::result::ZipError> {
+        provision::extract_zip(source_file, target_dir, progress)
     }
 
     #[cfg(feature = "pe")]
@@ -43,12 +70,7 @@ impl FfnxInstallation {
         };
         match pe_format::pe_version_info(target_dir.join(dll_name).as_path()) {
             Ok(infos) => Some(FfnxInstallation {
-                version: format!(
-                    "{}.{}.{}",
-                    infos.product_version.Major,
-                    infos.product_version.Minor,
-                    infos.product_version.Patch
-                ),
+                version: Self::version_from_pe(&infos.product_version),
                 path: PathBuf::from(target_dir),
                 exe_name: String::from(ff8_exe_name),
             }),
@@ -60,35 +82,75 @@ impl FfnxInstallation {
         }
     }
 
+    /// The build number is only kept for canary builds, releases have none
+    #[cfg(feature = "pe")]
+    fn version_from_pe(product_version: &pelite::image::VS_VERSION) -> Version {
+        let mut numbers = vec![
+            u64::from(product_version.Major),
+            u64::from(product_version.Minor),
+            u64::from(product_version.Patch),
+        ];
+        if product_version.Build != 0 {
+            numbers.push(u64::from(product_version.Build))
+        }
+        Version {
+            numbers,
+            prerelease: None,
+        }
+    }
+
+    /// Development builds are versioned 0.0.0
+    pub fn is_dev_build(&self) -> bool {
+        self.version.numbers.iter().all(|number| *number == 0)
+    }
+
     #[cfg(feature = "network")]
-    pub fn find_version_on_github(
-        repo_name: &str,
+    pub fn find_version(
+        source: &dyn ReleaseSource,
         edition: &Edition,
         update_channel: UpdateChannel,
-    ) -> String {
-        let last_release = match crate::github::find_last_release(repo_name) {
+    ) -> Result<Option<RemoteFile>, provision::Error> {
+        let last_release = match source.latest_releases() {
             Ok(last_release) => Some(last_release),
+            // The fallback would be outdated, better let the user retry later
+            Err(e @ provision::Error::RateLimited(_)) => return Err(e),
             Err(e) => {
-                warn!("Unable to find the last release from GitHub: {}", e);
+                warn!("Unable to find the last release from {}: {}", source, e);
                 None
             }
         };
 
-        let release = match update_channel {
-            UpdateChannel::Stable => last_release.and_then(|r| r.latest),
-            UpdateChannel::Beta => last_release.and_then(|r| r.latest_not_recent),
-            UpdateChannel::Alpha => last_release.and_then(|r| r.prerelease),
-        };
+        let release = last_release
+            .and_then(|last_release| Self::channel_release(last_release, update_channel));
 
-        release.and_then(|release| {
-            Self::find_asset_from_github_release(&release, edition)
-        }).map(|asset| asset.browser_download_url)
-        .unwrap_or_else(|| {
-            String::from(match edition {
-                Edition::Steam => "https://github.com/julianxhokaxhiu/FFNx/releases/download/canary/FFNx-Steam-v1.19.1.114.zip",
-                Edition::Standard | Edition::Remastered => "https://github.com/julianxhokaxhiu/FFNx/releases/download/canary/FFNx-FF8_2000-v1.19.1.114.zip",
+        Ok(release
+            .and_then(|release| {
+                Self::find_asset_from_github_release(&release, edition)
+                    .map(|asset| crate::github::remote_file_from_asset(&release, &asset))
             })
-        })
+            .or_else(|| source.fallback_file(edition)))
+    }
+
+    #[cfg(feature = "network")]
+    pub fn channel_release(
+        last_release: LatestRelease,
+        update_channel: UpdateChannel,
+    ) -> Option<GitHubRelease> {
+        match update_channel {
+            UpdateChannel::Stable => last_release.latest,
+            UpdateChannel::Beta => last_release.beta,
+            UpdateChannel::Alpha => last_release.prerelease,
+        }
+    }
+
+    /// The canary build to install when GitHub cannot be reached
+    #[cfg(feature = "network")]
+    pub fn canary_file(edition: &Edition) -> Option<RemoteFile> {
+        Some(RemoteFile::new(match edition {
+            Edition::Steam => "https://github.com/julianxhokaxhiu/FFNx/releases/download/canary/FFNx-Steam-v1.19.1.114.zip",
+            Edition::Standard => "https://github.com/julianxhokaxhiu/FFNx/releases/download/canary/FFNx-FF8_2000-v1.19.1.114.zip",
+            Edition::Remastered => return None,
+        }))
     }
 
     #[cfg(feature = "network")]
@@ -98,7 +160,8 @@ impl FfnxInstallation {
     ) -> Option<GitHubReleaseAsset> {
         let keyword = match edition {
             Edition::Steam => "steam",
-            Edition::Standard | Edition::Remastered => "ff8_2000",
+            Edition::Standard => "ff8_2000",
+            Edition::Remastered => return None,
         };
         release
             .assets
@@ -121,19 +184,27 @@ impl FfnxInstallation {
         self.path.join("FFNx.toml")
     }
 
+    pub fn staging_path(&self) -> PathBuf {
+        crate::provision::sibling_dir(&self.path, STAGING_DIR)
+    }
+
+    pub fn backup_path(&self) -> PathBuf {
+        crate::provision::sibling_dir(&self.path, BACKUP_DIR)
+    }
+
+    pub fn has_backup(&self) -> bool {
+        self.backup_path().is_dir()
+    }
+
     fn launch_game_directly(&self, ff8_path: &PathBuf) -> Result<Child, std::io::Error> {
         Installation::launch_game_directly(ff8_path, &self.path)
     }
 
-    pub fn launch_game(
-        &self,
-        game_installation: &Installation,
-        steam_exe: &Path,
-    ) -> std::io::Result<()> {
+    pub fn launch_game(&self, game_installation: &Installation) -> std::io::Result<()> {
         if let Err(e) = match game_installation.edition {
             Edition::Standard => self.launch_game_directly(&self.exe_path()),
             Edition::Steam | Edition::Remastered => game_installation
-                .launch_game_via_steam(&self.exe_path(), steam_exe, &self.path)
+                .launch_game_via_steam(&self.exe_path(), &self.path)
                 .or_else(|_| self.launch_game_directly(&game_installation.get_launcher_path())),
         } {
             error!("Unable to launch game: {}", e);
@@ -0,0 +1,279 @@
+use crate::game::installation::{Edition, Publisher, Version};
+use crate::provision;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Shipped with Mumba, see the header of the file for the format
+const DATABASE: &str = include_str!("../../data/exe_fingerprints.txt");
+
+#[derive(Error, Debug)]
+#[error("Invalid fingerprint at line {line}: {message}")]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Criterion {
+    /// Little-endian value at a file offset
+    Probe(u64, u32),
+    Size(u64),
+    Timestamp(u32),
+    Sha256(String),
+    Name(String),
+    /// Target CPU of the PE header
+    Machine(u16),
+    /// A file next to the executable, case insensitive
+    Sibling(String),
+}
+
+/// A known executable, recognized when all its criteria match
+#[derive(Clone, Debug)]
+pub struct Fingerprint {
+    pub edition: Edition,
+    pub version: Option<Version>,
+    pub publisher: Option<Publisher>,
+    pub criteria: Vec<Criterion>,
+}
+
+/// The fingerprints shipped with Mumba
+pub fn database() -> &'static [Fingerprint] {
+    static FINGERPRINTS: OnceLock<Vec<Fingerprint>> = OnceLock::new();
+    FINGERPRINTS.get_or_init(|| parse(DATABASE).expect("Valid fingerprint database"))
+}
+
+pub fn parse(text: &str) -> Result<Vec<Fingerprint>, ParseError> {
+    let mut fingerprints = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| ParseError {
+            line: index + 1,
+            message,
+        };
+        let mut columns = line.split_whitespace();
+        let (Some(edition), Some(version), Some(publisher)) =
+            (columns.next(), columns.next(), columns.next())
+        else {
+            return Err(error(String::from(
+                "Expected edition, version and publisher",
+            )));
+        };
+        let criteria = columns
+            .map(|criterion| {
+                parse_criterion(criterion)
+                    .ok_or_else(|| error(format!("Unknown criterion \"{}\"", criterion)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if criteria.is_empty() {
+            return Err(error(String::from("No criteria")));
+        }
+        fingerprints.push(Fingerprint {
+            edition: parse_edition(edition)
+                .ok_or_else(|| error(format!("Unknown edition \"{}\"", edition)))?,
+            version: parse_optional(version, parse_version)
+                .ok_or_else(|| error(format!("Unknown version \"{}\"", version)))?,
+            publisher: parse_optional(publisher, parse_publisher)
+                .ok_or_else(|| error(format!("Unknown publisher \"{}\"", publisher)))?,
+            criteria,
+        })
+    }
+    Ok(fingerprints)
+}
+
+/// Looks for the executable in the fingerprints shipped with Mumba
+pub fn identify(exe_path: &Path) -> std::io::Result<Option<&'static Fingerprint>> {
+    identify_in(database(), exe_path)
+}
+
+pub fn identify_in<'a>(
+    fingerprints: &'a [Fingerprint],
+    exe_path: &Path,
+) -> std::io::Result<Option<&'a Fingerprint>> {
+    let mut exe = Exe::open(exe_path)?;
+    for fingerprint in fingerprints {
+        if fingerprint.matches(&mut exe)? {
+            return Ok(Some(fingerprint));
+        }
+    }
+    Ok(None)
+}
+
+impl Fingerprint {
+    fn matches(&self, exe: &mut Exe) -> std::io::Result<bool> {
+        for criterion in &self.criteria {
+            let matches = match criterion {
+                Criterion::Probe(offset, value) => exe.read_u32(*offset)? == Some(*value),
+                Criterion::Size(size) => exe.size == *size,
+                Criterion::Timestamp(timestamp) => exe.timestamp()? == Some(*timestamp),
+                Criterion::Sha256(sha256) => exe.sha256()? == sha256.as_str(),
+                Criterion::Name(name) => exe.name.eq_ignore_ascii_case(name),
+                Criterion::Machine(machine) => exe.machine()? == Some(*machine),
+                Criterion::Sibling(name) => exe.has_sibling(name),
+            };
+            if !matches {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// The executable being identified, the hash is only computed when a fingerprint needs it
+struct Exe<'a> {
+    path: &'a Path,
+    file: File,
+    name: String,
+    size: u64,
+    sha256: Option<String>,
+}
+
+impl<'a> Exe<'a> {
+    fn open(path: &'a Path) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            size,
+            sha256: None,
+        })
+    }
+
+    /// None when the file is too short
+    fn read_u32(&mut self, offset: u64) -> std::io::Result<Option<u32>> {
+        if offset + 4 > self.size {
+            return Ok(None);
+        }
+        let mut bytes = [0u8; 4];
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut bytes)?;
+        Ok(Some(u32::from_le_bytes(bytes)))
+    }
+
+    /// The offset of the PE header, None when the file is not a PE executable
+    fn pe_offset(&mut self) -> std::io::Result<Option<u64>> {
+        let Some(pe_offset) = self.read_u32(0x3C)? else {
+            return Ok(None);
+        };
+        // "PE\0\0"
+        if self.read_u32(pe_offset as u64)? != Some(0x4550) {
+            return Ok(None);
+        }
+        Ok(Some(pe_offset as u64))
+    }
+
+    fn timestamp(&mut self) -> std::io::Result<Option<u32>> {
+        match self.pe_offset()? {
+            Some(pe_offset) => self.read_u32(pe_offset + 8),
+            None => Ok(None),
+        }
+    }
+
+    fn machine(&mut self) -> std::io::Result<Option<u16>> {
+        match self.pe_offset()? {
+            Some(pe_offset) => Ok(self.read_u32(pe_offset + 4)?.map(|value| value as u16)),
+            None => Ok(None),
+        }
+    }
+
+    fn has_sibling(&self, name: &str) -> bool {
+        let Some(Ok(entries)) = self.path.parent().map(std::fs::read_dir) else {
+            return false;
+        };
+        entries.flatten().any(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(name)
+        })
+    }
+
+    fn sha256(&mut self) -> std::io::Result<&str> {
+        if self.sha256.is_none() {
+            self.sha256 = Some(provision::sha256_file(self.path)?)
+        }
+        Ok(self.sha256.as_deref().unwrap_or_default())
+    }
+}
+
+fn parse_criterion(criterion: &str) -> Option<Criterion> {
+    if let Some(probe) = criterion.strip_prefix("probe:") {
+        let (offset, value) = probe.split_once('=')?;
+        return Some(Criterion::Probe(
+            parse_number(offset)?,
+            u32::try_from(parse_number(value)?).ok()?,
+        ));
+    }
+    let (key, value) = criterion.split_once('=')?;
+    match key {
+        "size" => Some(Criterion::Size(parse_number(value)?)),
+        "timestamp" => Some(Criterion::Timestamp(
+            u32::try_from(parse_number(value)?).ok()?,
+        )),
+        "sha256" if value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Some(Criterion::Sha256(value.to_ascii_lowercase()))
+        }
+        "name" if !value.is_empty() => Some(Criterion::Name(String::from(value))),
+        "machine" => Some(Criterion::Machine(
+            u16::try_from(parse_number(value)?).ok()?,
+        )),
+        "sibling" if !value.is_empty() => Some(Criterion::Sibling(String::from(value))),
+        _ => None,
+    }
+}
+
+fn parse_number(value: &str) -> Option<u64> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn parse_optional<T>(value: &str, parse: fn(&str) -> Option<T>) -> Option<Option<T>> {
+    if value == "-" {
+        Some(None)
+    } else {
+        parse(value).map(Some)
+    }
+}
+
+pub(crate) fn parse_edition(value: &str) -> Option<Edition> {
+    match value {
+        "standard" => Some(Edition::Standard),
+        "steam" => Some(Edition::Steam),
+        "remastered" => Some(Edition::Remastered),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_version(value: &str) -> Option<Version> {
+    match value {
+        "V100" => Some(Version::V100),
+        "V120" => Some(Version::V120),
+        "V120NV" => Some(Version::V120NV),
+        _ => None,
+    }
+}
+
+fn parse_publisher(value: &str) -> Option<Publisher> {
+    match value {
+        "EaJp" => Some(Publisher::EaJp),
+        "EaUs" => Some(Publisher::EaUs),
+        "EidosDe" => Some(Publisher::EidosDe),
+        "EidosFr" => Some(Publisher::EidosFr),
+        "EidosIt" => Some(Publisher::EidosIt),
+        "EidosSp" => Some(Publisher::EidosSp),
+        "EidosUk" => Some(Publisher::EidosUk),
+        _ => None,
+    }
+}
@@ -0,0 +1,527 @@
+use crate::game::env::Env;
+use crate::game::install_manifest::{self, InstallManifest, ManifestEntry};
+use crate::provision::{self, Progress, RemoteFile};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const JOURNAL_FILE: &str = "install.txt";
+/// Copies of the files overwritten by the steps, named after the step number
+const JOURNAL_FILES_DIR: &str = "files";
+
+/// Where the file of a step comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Copy(PathBuf),
+    Rename(PathBuf),
+    /// One step per extracted file, with the archive as source
+    Extract(PathBuf),
+    /// The target directory, swapped with `provision::swap_dir`
+    Swap {
+        staging_dir: PathBuf,
+        backup_dir: PathBuf,
+    },
+    /// The target modified in place
+    Patch,
+}
+
+impl Action {
+    /// The name in the journal file and the source, if any
+    fn describe(&self) -> (&'static str, Option<&Path>) {
+        match self {
+            Action::Copy(source) => ("copy", Some(source)),
+            Action::Rename(source) => ("rename", Some(source)),
+            Action::Extract(source) => ("extract", Some(source)),
+            Action::Swap { staging_dir, .. } => ("swap", Some(staging_dir)),
+            Action::Patch => ("patch", None),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub action: Action,
+    pub target: PathBuf,
+    /// SHA-256 of the target before the step, None when it did not exist or is a directory
+    pub before: Option<String>,
+    /// SHA-256 of the target after the step, "-" for a directory, None until it is done
+    pub after: Option<String>,
+}
+
+impl Step {
+    pub fn is_done(&self) -> bool {
+        self.after.is_some()
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, source) = self.action.describe();
+        write!(f, "{}", name)?;
+        if let Some(source) = source {
+            write!(f, " \"{}\" to", source.to_string_lossy())?
+        }
+        write!(f, " \"{}\"", self.target.to_string_lossy())?;
+        match (&self.action, &self.after) {
+            (Action::Swap { .. }, Some(_)) => write!(f, " (done)"),
+            (Action::Swap { .. }, None) => write!(f, " (not done)"),
+            _ => write!(
+                f,
+                " ({} -> {})",
+                self.before.as_deref().unwrap_or("new"),
+                self.after.as_deref().unwrap_or("not done")
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum State {
+    Empty,
+    /// Not committed yet, the install was interrupted when it is not running
+    InProgress,
+    Committed,
+    Undone,
+}
+
+/// Records the provisioning steps of the last install, with the hashes of the files before and after
+/// each step, to resume or undo an interrupted install. Kept until the next install for support.
+/// The files created in the target directory are recorded in its `InstallManifest` as the steps
+/// are done, undoing an interrupted install also undoes what it recorded there.
+pub struct InstallJournal {
+    dir: PathBuf,
+    state: State,
+    target_dir: PathBuf,
+    started_at: String,
+    /// The number of entries of the manifest before the install
+    manifest_entries: usize,
+    steps: Vec<Step>,
+    resumed: bool,
+    manifest: Option<InstallManifest>,
+}
+
+impl InstallJournal {
+    /// Reads the journal of the last install
+    pub fn new(dir: PathBuf) -> std::io::Result<Self> {
+        let mut journal = Self {
+            dir,
+            state: State::Empty,
+            target_dir: PathBuf::new(),
+            started_at: String::new(),
+            manifest_entries: 0,
+            steps: Vec::new(),
+            resumed: false,
+            manifest: None,
+        };
+        let contents = match std::fs::read_to_string(journal.dir.join(JOURNAL_FILE)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(journal),
+            Err(e) => return Err(e),
+        };
+        for line in contents.lines() {
+            if journal.parse_line(line).is_none() {
+                warn!("Ignore invalid journal line: {}", line)
+            }
+        }
+        Ok(journal)
+    }
+
+    pub fn from_env(env: &Env) -> std::io::Result<Self> {
+        Self::new(env.data_dir.join("journal"))
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// The FFNx directory of the install
+    pub fn target_dir(&self) -> &Path {
+        &self.target_dir
+    }
+
+    pub fn started_at(&self) -> &str {
+        &self.started_at
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Whether `begin` resumed an interrupted install
+    pub fn is_resumed(&self) -> bool {
+        self.resumed
+    }
+
+    /// The manifest of the target directory
+    pub fn manifest(&mut self) -> std::io::Result<&mut InstallManifest> {
+        let manifest = match self.manifest.take() {
+            Some(manifest) => manifest,
+            None => InstallManifest::open(&self.target_dir)?,
+        };
+        Ok(self.manifest.insert(manifest))
+    }
+
+    /// Starts recording an install in `target_dir`, created when needed,
+    /// an interrupted install in the same directory is resumed instead
+    pub fn begin(&mut self, target_dir: &Path) -> std::io::Result<()> {
+        self.resumed = self.state == State::InProgress && self.target_dir == target_dir;
+        if self.resumed {
+            info!(
+                "Resume the install in \"{}\" started at {}",
+                target_dir.to_string_lossy(),
+                self.started_at
+            );
+            self.manifest = Some(InstallManifest::create(target_dir)?);
+            return Ok(());
+        }
+        if self.state == State::InProgress {
+            warn!(
+                "Forget the interrupted install in \"{}\"",
+                self.target_dir.to_string_lossy()
+            )
+        }
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => (),
+        }
+        std::fs::create_dir_all(self.dir.join(JOURNAL_FILES_DIR))?;
+        self.state = State::InProgress;
+        self.target_dir = target_dir.to_path_buf();
+        self.started_at = jiff::Timestamp::now().to_string();
+        self.manifest_entries = InstallManifest::open(target_dir)?.entries().len();
+        self.steps.clear();
+        self.append(&format!(
+            "install\t{}\t{}\t{}",
+            self.target_dir.to_string_lossy(),
+            self.started_at,
+            self.manifest_entries
+        ))?;
+        self.manifest = Some(InstallManifest::create(target_dir)?);
+        Ok(())
+    }
+
+    pub fn copy_file(&mut self, source: &Path, target: &Path) -> std::io::Result<()> {
+        let action = Action::Copy(source.to_path_buf());
+        if self.is_already_done(&action, target)? {
+            return Ok(());
+        }
+        let index = self.start_step(action, target)?;
+        provision::copy_file(&source.to_path_buf(), &target.to_path_buf())?;
+        self.finish_step(index)
+    }
+
+    pub fn rename_file(&mut self, source: &Path, target: &Path) -> std::io::Result<()> {
+        let action = Action::Rename(source.to_path_buf());
+        if !source.exists() && self.is_already_done(&action, target)? {
+            return Ok(());
+        }
+        let index = self.start_step(action, target)?;
+        provision::rename_file(&source.to_path_buf(), &target.to_path_buf())?;
+        self.finish_step(index)
+    }
+
+    /// Modifies the target in place with `patch`
+    pub fn patch_file<E: From<std::io::Error>>(
+        &mut self,
+        target: &Path,
+        patch: impl FnOnce(&Path) -> Result<bool, E>,
+    ) -> Result<(), E> {
+        if self.is_already_done(&Action::Patch, target)? {
+            return Ok(());
+        }
+        let index = self.start_step(Action::Patch, target)?;
+        patch(target)?;
+        Ok(self.finish_step(index)?)
+    }
+
+    /// Like `provision::swap_dir`, the files it adds to `target_dir` are recorded in the manifest.
+    /// A swap interrupted halfway is put back first
+    pub fn swap_dir(
+        &mut self,
+        staging_dir: &Path,
+        target_dir: &Path,
+        backup_dir: &Path,
+    ) -> std::io::Result<()> {
+        if self.is_swapped(staging_dir, target_dir) {
+            info!("Already swapped: \"{}\"", target_dir.to_string_lossy());
+            return Ok(());
+        }
+        let action = Action::Swap {
+            staging_dir: staging_dir.to_path_buf(),
+            backup_dir: backup_dir.to_path_buf(),
+        };
+        if self
+            .steps
+            .iter()
+            .any(|step| step.action == action && step.target == target_dir)
+        {
+            install_manifest::undo_entry(&ManifestEntry::Swapped {
+                target_dir: target_dir.to_path_buf(),
+                backup_dir: backup_dir.to_path_buf(),
+            })?
+        }
+        let manifest = self.manifest()?;
+        if !manifest.created_dir(target_dir) {
+            for file in provision::list_files(staging_dir)? {
+                let path = target_dir.join(file);
+                if !path.exists() {
+                    manifest.record(ManifestEntry::CreatedFile(path))?
+                }
+            }
+        }
+        let index = self.start_step(action, target_dir)?;
+        provision::swap_dir(staging_dir, target_dir, backup_dir)?;
+        self.finish_step(index)
+    }
+
+    /// Whether the interrupted install already swapped `staging_dir` into `target_dir`
+    pub fn is_swapped(&self, staging_dir: &Path, target_dir: &Path) -> bool {
+        self.steps.iter().any(|step| {
+            step.is_done()
+                && step.target == target_dir
+                && matches!(&step.action, Action::Swap { staging_dir: dir, .. } if dir == staging_dir)
+        })
+    }
+
+    /// Skipped when every file of the archive was already extracted by the interrupted install
+    pub fn extract_zip(
+        &mut self,
+        archive: &Path,
+        target_dir: &Path,
+        progress: &dyn Fn(Progress),
+    ) -> Result<(), zip::result::ZipError> {
+        let files = provision::list_zip_files(archive)?;
+        let mut indexes = Vec::new();
+        let mut all_done = !files.is_empty();
+        for file in &files {
+            let action = Action::Extract(archive.to_path_buf());
+            let target = target_dir.join(file);
+            if !self.is_already_done(&action, &target)? {
+                all_done = false;
+                indexes.push(self.start_step(action, &target)?)
+            }
+        }
+        if all_done {
+            return Ok(());
+        }
+        provision::extract_zip(archive, target_dir, progress)?;
+        for index in indexes {
+            self.finish_step(index)?
+        }
+        Ok(())
+    }
+
+    /// Like `provision::download_zip`, with the extraction recorded
+    pub fn download_zip(
+        &mut self,
+        remote: &RemoteFile,
+        target_dir: &Path,
+        env: &Env,
+        progress: &dyn Fn(Progress),
+    ) -> Result<(), provision::Error> {
+        let archive_path = provision::download(remote, env, progress)?;
+        self.extract_zip(&archive_path, target_dir, progress)
+            .map_err(|e| {
+                provision::discard_archive(remote, &archive_path, &e);
+                provision::Error::from(e)
+            })
+    }
+
+    /// The install is over, the journal is kept until the next one
+    pub fn commit(&mut self) -> std::io::Result<()> {
+        self.append("commit")?;
+        self.state = State::Committed;
+        Ok(())
+    }
+
+    /// Puts back the files as they were before the steps, the most recent first,
+    /// then undoes what the install recorded in the manifest
+    pub fn undo(&mut self) -> std::io::Result<()> {
+        for (index, step) in self.steps.iter().enumerate().rev() {
+            info!("Undo: {}", step);
+            self.undo_step(index, step)?
+        }
+        let manifest_entries = self.manifest_entries;
+        self.manifest()?.undo_since(manifest_entries)?;
+        self.append("undone")?;
+        self.state = State::Undone;
+        Ok(())
+    }
+
+    fn undo_step(&self, index: usize, step: &Step) -> std::io::Result<()> {
+        match &step.action {
+            Action::Swap { backup_dir, .. } => {
+                return install_manifest::undo_entry(&ManifestEntry::Swapped {
+                    target_dir: step.target.clone(),
+                    backup_dir: backup_dir.clone(),
+                })
+            }
+            Action::Rename(source) if !source.exists() && step.target.exists() => {
+                provision::rename_file(&step.target, source)?
+            }
+            _ => (),
+        }
+        if step.before.is_none() {
+            return match std::fs::remove_file(&step.target) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let backup = self.backup_path(index);
+        if backup.exists() {
+            provision::copy_file(&backup, &step.target.to_path_buf())?
+        } else {
+            warn!(
+                "No copy of \"{}\" to restore",
+                step.target.to_string_lossy()
+            )
+        }
+        Ok(())
+    }
+
+    /// A step of the interrupted install, whose target did not change since
+    fn is_already_done(&self, action: &Action, target: &Path) -> std::io::Result<bool> {
+        let Some(step) = self
+            .steps
+            .iter()
+            .rev()
+            .find(|step| step.is_done() && step.action == *action && step.target == target)
+        else {
+            return Ok(false);
+        };
+        if hash(target)? != step.after {
+            return Ok(false);
+        }
+        info!("Already done: {}", step);
+        Ok(true)
+    }
+
+    /// Keeps a copy of the target file when it exists, to undo the step
+    fn start_step(&mut self, action: Action, target: &Path) -> std::io::Result<usize> {
+        let index = self.steps.len();
+        let before = match action {
+            Action::Swap { .. } => None,
+            _ => hash(target)?,
+        };
+        if before.is_some() {
+            std::fs::copy(target, self.backup_path(index))?;
+        }
+        let step = Step {
+            action,
+            target: target.to_path_buf(),
+            before,
+            after: None,
+        };
+        let (name, source) = step.action.describe();
+        let mut line = format!(
+            "step\t{}\t{}\t{}\t{}\t{}",
+            index,
+            name,
+            source.map_or(Cow::Borrowed("-"), Path::to_string_lossy),
+            step.target.to_string_lossy(),
+            step.before.as_deref().unwrap_or("-")
+        );
+        if let Action::Swap { backup_dir, .. } = &step.action {
+            line.push_str(&format!("\t{}", backup_dir.to_string_lossy()))
+        }
+        self.append(&line)?;
+        self.steps.push(step);
+        Ok(index)
+    }
+
+    /// A file created in the target directory is recorded in the manifest
+    fn finish_step(&mut self, index: usize) -> std::io::Result<()> {
+        let step = &self.steps[index];
+        let created = step.before.is_none()
+            && matches!(
+                step.action,
+                Action::Copy(_) | Action::Rename(_) | Action::Extract(_)
+            )
+            && step.target.starts_with(&self.target_dir);
+        let after = match step.action {
+            Action::Swap { .. } => String::from("-"),
+            _ => hash(&step.target)?.unwrap_or_else(|| String::from("-")),
+        };
+        if created {
+            let (path, target_dir) = (step.target.clone(), self.target_dir.clone());
+            let manifest = self.manifest()?;
+            if !manifest.created_dir(&target_dir) {
+                manifest.record(ManifestEntry::CreatedFile(path))?
+            }
+        }
+        self.append(&format!("done\t{}\t{}", index, after))?;
+        self.steps[index].after = Some(after);
+        Ok(())
+    }
+
+    fn backup_path(&self, index: usize) -> PathBuf {
+        self.dir.join(JOURNAL_FILES_DIR).join(index.to_string())
+    }
+
+    /// Written right away, to survive a crash
+    fn append(&self, line: &str) -> std::io::Result<()> {
+        let mut file = std::fs::File::options()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(JOURNAL_FILE))?;
+        writeln!(file, "{}", line)?;
+        file.sync_data()
+    }
+
+    fn parse_line(&mut self, line: &str) -> Option<()> {
+        let mut columns = line.split('\t');
+        match columns.next()? {
+            "install" => {
+                self.target_dir = PathBuf::from(columns.next()?);
+                self.started_at = String::from(columns.next().unwrap_or_default());
+                self.manifest_entries = columns.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+                self.state = State::InProgress;
+                self.steps.clear()
+            }
+            "step" => {
+                let index: usize = columns.next()?.parse().ok()?;
+                let (name, source) = (columns.next()?, PathBuf::from(columns.next()?));
+                let target = PathBuf::from(columns.next()?);
+                let before = columns.next()?;
+                if index != self.steps.len() {
+                    return None;
+                }
+                self.steps.push(Step {
+                    action: match name {
+                        "copy" => Action::Copy(source),
+                        "rename" => Action::Rename(source),
+                        "extract" => Action::Extract(source),
+                        "swap" => Action::Swap {
+                            staging_dir: source,
+                            backup_dir: PathBuf::from(columns.next()?),
+                        },
+                        "patch" => Action::Patch,
+                        _ => return None,
+                    },
+                    target,
+                    before: (before != "-").then(|| String::from(before)),
+                    after: None,
+                })
+            }
+            "done" => {
+                let index: usize = columns.next()?.parse().ok()?;
+                let after = columns.next()?;
+                self.steps.get_mut(index)?.after = Some(String::from(after))
+            }
+            "commit" => self.state = State::Committed,
+            "undone" => self.state = State::Undone,
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+/// None when the file does not exist
+fn hash(path: &Path) -> std::io::Result<Option<String>> {
+    match provision::sha256_file(path) {
+        Ok(sha256) => Ok(Some(sha256)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
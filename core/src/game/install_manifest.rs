@@ -0,0 +1,248 @@
+use crate::game::ffnx_installation;
+#[cfg(feature = "pe")]
+use crate::pe_format::{self, PePatch};
+use crate::provision;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Written in the FFNx directory, next to the files it lists
+const MANIFEST_FILE: &str = ".mumba-manifest";
+/// Suffix of the directory next to the FFNx one,
+/// with the game files replaced when FFNx is installed in an existing directory
+const ORIGINAL_FILES_DIR: &str = ".mumba-original";
+
+/// A change made by Mumba outside of what it can recreate
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ManifestEntry {
+    /// Removed with all its contents
+    CreatedDir(PathBuf),
+    CreatedFile(PathBuf),
+    /// The backup is moved back in place
+    Replaced {
+        path: PathBuf,
+        backup: PathBuf,
+    },
+    /// Files moved by `provision::swap_dir`, put back with `provision::restore_backup`
+    Swapped {
+        target_dir: PathBuf,
+        backup_dir: PathBuf,
+    },
+    /// The 4GB patch, reversed by `pe_format::pe_unpatch` from the `pe_format::pe_backup_path` copy
+    Patched(PathBuf),
+}
+
+/// Every file Mumba created or replaced for a game, in the order of the changes
+pub struct InstallManifest {
+    path: PathBuf,
+    entries: Vec<ManifestEntry>,
+}
+
+impl InstallManifest {
+    /// Empty when nothing was recorded in this FFNx directory
+    pub fn open(ffnx_dir: &Path) -> std::io::Result<Self> {
+        let path = ffnx_dir.join(MANIFEST_FILE);
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents.lines().filter_map(parse_entry).collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { path, entries })
+    }
+
+    /// Creates the FFNx directory when needed, and records it
+    pub fn create(ffnx_dir: &Path) -> std::io::Result<Self> {
+        let created = !ffnx_dir.exists();
+        std::fs::create_dir_all(ffnx_dir)?;
+        let mut manifest = Self::open(ffnx_dir)?;
+        if created {
+            manifest.record(ManifestEntry::CreatedDir(ffnx_dir.to_path_buf()))?
+        }
+        Ok(manifest)
+    }
+
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the directory is removed on uninstall, then its files do not need to be recorded
+    pub fn created_dir(&self, dir: &Path) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| matches!(entry, ManifestEntry::CreatedDir(path) if path == dir))
+    }
+
+    /// Where `provision::swap_dir` keeps the replaced game files
+    pub fn original_files_path(ffnx_dir: &Path) -> PathBuf {
+        provision::sibling_dir(ffnx_dir, ORIGINAL_FILES_DIR)
+    }
+
+    /// Appended to the file right away, an entry already recorded is ignored
+    pub fn record(&mut self, entry: ManifestEntry) -> std::io::Result<()> {
+        if self.entries.contains(&entry) {
+            return Ok(());
+        }
+        let mut file = std::fs::File::options()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", format_entry(&entry))?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Undoes the changes, the most recent first. On error, what remains to undo is kept
+    pub fn undo(mut self) -> std::io::Result<()> {
+        let ffnx_dir = self
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        self.undo_since(0)?;
+        for suffix in [
+            ffnx_installation::STAGING_DIR,
+            ffnx_installation::BACKUP_DIR,
+        ] {
+            remove_dir(&provision::sibling_dir(&ffnx_dir, suffix))?
+        }
+        if ffnx_dir.exists() {
+            remove_file(&self.path)?
+        }
+        Ok(())
+    }
+
+    /// Undoes the changes recorded after the first `count` entries, the most recent first
+    pub fn undo_since(&mut self, count: usize) -> std::io::Result<()> {
+        while self.entries.len() > count {
+            let entry = &self.entries[self.entries.len() - 1];
+            if let Err(e) = undo_entry(entry) {
+                error!("Cannot undo {}: {}", format_entry(entry), e);
+                self.save()?;
+                return Err(e);
+            }
+            self.entries.pop();
+        }
+        // Gone with the directory it was created in
+        if self.path.exists() {
+            self.save()?
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|entry| format!("{}\n", format_entry(entry)))
+            .collect();
+        std::fs::write(&self.path, contents)
+    }
+}
+
+pub(crate) fn undo_entry(entry: &ManifestEntry) -> std::io::Result<()> {
+    match entry {
+        ManifestEntry::CreatedDir(path) => remove_dir(path),
+        ManifestEntry::CreatedFile(path) => remove_file(path),
+        ManifestEntry::Replaced { path, backup } => {
+            if backup.exists() {
+                provision::rename_file(backup, path)?
+            }
+            Ok(())
+        }
+        ManifestEntry::Swapped {
+            target_dir,
+            backup_dir,
+        } => {
+            if backup_dir.exists() {
+                provision::restore_backup(target_dir, backup_dir)?
+            }
+            Ok(())
+        }
+        ManifestEntry::Patched(path) => unpatch(path),
+    }
+}
+
+#[cfg(feature = "pe")]
+fn unpatch(path: &Path) -> std::io::Result<()> {
+    match pe_format::pe_unpatch(path, PePatch::LARGE_ADDRESS_AWARE) {
+        Ok(_) => Ok(()),
+        Err(pe_format::Error::IoError(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(pe_format::Error::IoError(e)) => Err(e),
+        Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+    }
+}
+
+#[cfg(not(feature = "pe"))]
+fn unpatch(path: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!(
+            "Cannot reverse the patch of \"{}\" without PE support",
+            path.to_string_lossy()
+        ),
+    ))
+}
+
+fn remove_dir(path: &Path) -> std::io::Result<()> {
+    info!("Remove \"{}\"", path.to_string_lossy());
+    match std::fs::remove_dir_all(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn remove_file(path: &Path) -> std::io::Result<()> {
+    info!("Remove \"{}\"", path.to_string_lossy());
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// One line per entry, the paths are separated by tabulations
+fn format_entry(entry: &ManifestEntry) -> String {
+    match entry {
+        ManifestEntry::CreatedDir(path) => format!("dir\t{}", path.to_string_lossy()),
+        ManifestEntry::CreatedFile(path) => format!("file\t{}", path.to_string_lossy()),
+        ManifestEntry::Replaced { path, backup } => format!(
+            "replaced\t{}\t{}",
+            path.to_string_lossy(),
+            backup.to_string_lossy()
+        ),
+        ManifestEntry::Swapped {
+            target_dir,
+            backup_dir,
+        } => format!(
+            "swapped\t{}\t{}",
+            target_dir.to_string_lossy(),
+            backup_dir.to_string_lossy()
+        ),
+        ManifestEntry::Patched(path) => format!("patched\t{}", path.to_string_lossy()),
+    }
+}
+
+fn parse_entry(line: &str) -> Option<ManifestEntry> {
+    let mut columns = line.split('\t');
+    let kind = columns.next()?;
+    let path = PathBuf::from(columns.next()?);
+    Some(match kind {
+        "dir" => ManifestEntry::CreatedDir(path),
+        "file" => ManifestEntry::CreatedFile(path),
+        "replaced" => ManifestEntry::Replaced {
+            path,
+            backup: PathBuf::from(columns.next()?),
+        },
+        "swapped" => ManifestEntry::Swapped {
+            target_dir: path,
+            backup_dir: PathBuf::from(columns.next()?),
+        },
+        "patched" => ManifestEntry::Patched(path),
+        _ => {
+            warn!("Unknown manifest entry: {}", line);
+            return None;
+        }
+    })
+}
@@ -1,28 +1,50 @@
-#[cfg(any(feature = "pe", feature = "zip"))]
+use crate::game::discovery;
+#[cfg(any(feature = "pe", all(feature = "network", feature = "zip")))]
 use crate::game::env::Env;
+use crate::game::fingerprint;
 #[cfg(windows)]
 use crate::os::regedit;
 use crate::os::run_helper;
 #[cfg(feature = "pe")]
 use crate::pe_format;
-#[cfg(any(feature = "pe", feature = "zip"))]
+#[cfg(feature = "zip")]
 use crate::provision;
 use crate::steam;
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
 use std::str::FromStr;
 use thiserror::Error;
 
-#[derive(Clone, Debug)]
+const REMASTERED_EXE_NAME: &str = "FFVIII.exe";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Edition {
     Standard,
     Steam = 39150,
     Remastered = 1026680,
 }
 
-#[derive(Clone, Debug)]
+/// What Mumba can set up for a game edition, see `Edition::supports`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// Installing, upgrading and launching FFNx
+    Ffnx,
+    /// Replacing the game launcher by the one of Mumba
+    ReplaceLauncher,
+}
+
+impl Edition {
+    pub fn supports(&self, feature: Feature) -> bool {
+        match self {
+            Edition::Standard => matches!(feature, Feature::Ffnx),
+            Edition::Steam => matches!(feature, Feature::Ffnx | Feature::ReplaceLauncher),
+            // FFNx only targets the 2000 and Steam releases, the Remastered engine is different
+            Edition::Remastered => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Version {
     Unknown,
     V100,
@@ -41,14 +63,35 @@ pub enum Publisher {
     EidosUk,
 }
 
+/// Where an installation was found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstallationSource {
+    /// Selected by the user
+    Manual,
+    /// The key written by the installer of the 2000 release
+    Registry,
+    Steam,
+    Gog,
+    Heroic,
+    Lutris,
+    Bottles,
+    Wine,
+}
+
 #[derive(Clone)]
 pub struct Installation {
     pub app_path: PathBuf,
     pub exe_name: String,
     pub edition: Edition,
     pub version: Option<(Version, Publisher)>,
+    /// Empty when unknown
     pub language: String,
     pub config_path: PathBuf,
+    pub source: InstallationSource,
+    /// Steam and Remastered editions only
+    pub app_manifest: Option<steam::AppManifest>,
+    /// The root and the kind of the Steam client owning the game, Steam and Remastered editions only
+    pub steam_client: Option<(PathBuf, steam::SteamClient)>,
 }
 
 #[derive(Error, Debug)]
@@ -71,13 +114,21 @@ impl Installation {
             .map(|e| e.to_path_buf())
             .unwrap_or_default();
         // Detect edition and language
-        let (edition, language) = match Self::get_steam_edition_lang(&app_path) {
-            Ok(lang) => (Edition::Steam, lang),
-            Err(_) => (
-                Edition::Standard,
-                Self::get_standard_edition_lang(&app_path).unwrap_or(String::from("eng")),
-            ),
+        let (edition, language) = if app_path.join(REMASTERED_EXE_NAME).exists() {
+            (Edition::Remastered, None)
+        } else {
+            match Self::get_steam_edition_lang(&app_path) {
+                Ok(lang) => (Edition::Steam, Some(lang)),
+                Err(_) => (
+                    Edition::Standard,
+                    Some(Self::get_standard_edition_lang(&app_path).unwrap_or(String::from("eng"))),
+                ),
+            }
         };
+        let app_manifest = Self::read_app_manifest(&edition, &app_path);
+        let language = language
+            .or_else(|| Self::get_remastered_edition_lang(app_manifest.as_ref()))
+            .unwrap_or_default();
 
         let mut exe_name = String::from(
             exe_path
@@ -88,10 +139,11 @@ impl Installation {
         let lower_exe_name = exe_name.to_ascii_lowercase();
 
         if lower_exe_name.contains("launcher") || lower_exe_name.contains("chocobo") {
-            if !matches!(edition, Edition::Steam) {
-                return Err(FromExeError::LauncherSelected);
-            }
-            exe_name = format!("FF8_{}.exe", language);
+            exe_name = match edition {
+                Edition::Standard => return Err(FromExeError::LauncherSelected),
+                Edition::Steam => format!("FF8_{}.exe", language),
+                Edition::Remastered => String::from(REMASTERED_EXE_NAME),
+            };
             if !app_path.join(&exe_name).exists() {
                 return Err(FromExeError::LauncherSelected);
             }
@@ -100,6 +152,7 @@ impl Installation {
         // Detect version
         let version = Self::get_version_from_exe(&app_path.join(&exe_name)).unwrap_or(None);
         let config_path = Self::get_config_path(&edition, &app_path);
+        let steam_client = Self::find_steam_client(&edition, &app_path);
 
         Ok(Self {
             app_path,
@@ -108,11 +161,15 @@ impl Installation {
             version,
             language,
             config_path,
+            source: InstallationSource::Manual,
+            app_manifest,
+            steam_client,
         })
     }
 
     pub fn from_directory<P: AsRef<Path>>(app_path: P, edition: Edition) -> Option<Self> {
         let app_path = app_path.as_ref();
+        let app_manifest = Self::read_app_manifest(&edition, app_path);
         // Detect exe name and lang
         let (exe_name, language) = match edition {
             Edition::Standard => (
@@ -128,8 +185,8 @@ impl Installation {
                 (exe_name, language)
             }
             Edition::Remastered => (
-                String::from("FFVIII.exe"),
-                String::from("en"), // TODO: lang
+                String::from(REMASTERED_EXE_NAME),
+                Self::get_remastered_edition_lang(app_manifest.as_ref()).unwrap_or_default(),
             ),
         };
         // Detect version
@@ -139,6 +196,7 @@ impl Installation {
         if !app_path.join(&exe_name).exists() {
             return None;
         }
+        let steam_client = Self::find_steam_client(&edition, app_path);
 
         Some(Self {
             app_path: PathBuf::from(app_path),
@@ -147,9 +205,98 @@ impl Installation {
             version,
             language,
             config_path,
+            source: InstallationSource::Manual,
+            app_manifest,
+            steam_client,
         })
     }
 
+    /// Detects the edition from the executables found in the directory, ignoring their case
+    pub fn find_in_directory<P: AsRef<Path>>(
+        app_path: P,
+        source: InstallationSource,
+    ) -> Option<Self> {
+        let file_names: Vec<String> = std::fs::read_dir(app_path.as_ref())
+            .ok()?
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        // The Steam launcher leads to the executable of the game language
+        [REMASTERED_EXE_NAME, "FF8_Launcher.exe", "FF8.exe"]
+            .iter()
+            .filter_map(|exe_name| {
+                file_names
+                    .iter()
+                    .find(|file_name| file_name.eq_ignore_ascii_case(exe_name))
+            })
+            .find_map(|file_name| Self::from_exe_path(app_path.as_ref().join(file_name)).ok())
+            .map(|installation| installation.with_source(source))
+    }
+
+    /// The manifest is in the `steamapps` directory of the library
+    #[cfg(feature = "steam")]
+    fn read_app_manifest(edition: &Edition, app_path: &Path) -> Option<steam::AppManifest> {
+        if matches!(edition, Edition::Standard) {
+            return None;
+        }
+        let steamapps_dir = app_path.join("..").join("..");
+        let manifest_path = steam::AppManifest::path(&steamapps_dir, edition.clone() as u64);
+        match steam::AppManifest::from_file(&manifest_path) {
+            Ok(app_manifest) => Some(app_manifest),
+            Err(e) => {
+                warn!(
+                    "Cannot read Steam manifest \"{}\": {}",
+                    manifest_path.to_string_lossy(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "steam"))]
+    fn read_app_manifest(_edition: &Edition, _app_path: &Path) -> Option<steam::AppManifest> {
+        None
+    }
+
+    /// The client whose libraries contain the game, else the first one found
+    fn find_steam_client(
+        edition: &Edition,
+        app_path: &Path,
+    ) -> Option<(PathBuf, steam::SteamClient)> {
+        if *edition == Edition::Standard {
+            return None;
+        }
+        let steam = steam::Steam::find_owner(app_path)
+            .or_else(|| steam::Steam::all().into_iter().next())?;
+        info!(
+            "Use {:?} Steam client at \"{}\"",
+            steam.client,
+            steam.path.to_string_lossy()
+        );
+        Some((steam.path, steam.client))
+    }
+
+    /// The command starting the Steam client of the game, empty for the Standard edition
+    pub fn steam_exe(&self) -> PathBuf {
+        self.steam_client
+            .as_ref()
+            .map(|(path, client)| steam::steam_exe(path, *client))
+            .unwrap_or_default()
+    }
+
+    /// Steam is downloading or waiting to install an update of the game
+    pub fn is_updating(&self) -> bool {
+        self.app_manifest
+            .as_ref()
+            .is_some_and(|app_manifest| app_manifest.state() == steam::AppState::Updating)
+    }
+
+    pub fn with_source(mut self, source: InstallationSource) -> Self {
+        self.source = source;
+        self
+    }
+
     pub fn exe_path(&self) -> PathBuf {
         PathBuf::from(&self.app_path).join(&self.exe_name)
     }
@@ -160,19 +307,45 @@ impl Installation {
         if let Some(installation) = Self::search_original_version() {
             installations.push(installation)
         };
-        let steam_library_folders = match steam::Steam::from_config() {
-            Ok(lib_folders) => Some(lib_folders),
-            Err(e) => {
-                warn!("Cannot find Steam Client installation: {}", e);
-                None
+        let mut steam_clients: Vec<Option<steam::Steam>> =
+            steam::Steam::all().into_iter().map(Some).collect();
+        if steam_clients.is_empty() {
+            warn!("Cannot find Steam Client installation");
+            // Uninstall entries can still be found on Windows
+            steam_clients.push(None)
+        }
+        for steam_client in &steam_clients {
+            if let Some(steam) = steam_client {
+                info!(
+                    "Search in {:?} Steam client at \"{}\"",
+                    steam.client,
+                    steam.path.to_string_lossy()
+                )
             }
-        };
-        if let Some(installation) = Self::search_steam_edition(&steam_library_folders) {
-            installations.push(installation)
-        };
-        if let Some(installation) = Self::search_remastered_edition(&steam_library_folders) {
-            installations.push(installation)
-        };
+            for installation in [
+                Self::search_steam_edition(steam_client),
+                Self::search_remastered_edition(steam_client),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                push_installation(
+                    &mut installations,
+                    installation.with_source(InstallationSource::Steam),
+                )
+            }
+        }
+        for installation in discovery::search() {
+            push_installation(&mut installations, installation)
+        }
+        for installation in &installations {
+            info!(
+                "Found {:?} edition at \"{}\" ({:?})",
+                installation.edition,
+                installation.app_path.to_string_lossy(),
+                installation.source
+            )
+        }
         installations
     }
 
@@ -198,55 +371,32 @@ impl Installation {
         Ok(contents.to_ascii_uppercase())
     }
 
-    pub fn get_version_from_exe(
-        exe_path: &PathBuf,
-    ) -> std::io::Result<Option<(Version, Publisher)>> {
-        let mut f = File::options().read(true).write(false).open(exe_path)?;
-        f.seek(SeekFrom::Start(0x1004))?;
-        let mut bytes = [0u8; 4];
-        f.read_exact(&mut bytes)?;
-        let version_check1 = u32::from_le_bytes(bytes);
-        f.seek(SeekFrom::Start(0x1404))?;
-        f.read_exact(&mut bytes)?;
-        let version_check2 = u32::from_le_bytes(bytes);
-
-        if version_check1 == 0x3885048D && version_check2 == 0x159618 {
-            Ok(Some((Version::V120, Publisher::EaUs)))
-        } else if version_check1 == 0x3885048D && version_check2 == 0x1597C8 {
-            Ok(Some((Version::V120NV, Publisher::EaUs)))
-        } else if version_check1 == 0x1085048D && version_check2 == 0x159B48 {
-            Ok(Some((Version::V120, Publisher::EidosFr)))
-        } else if version_check1 == 0x1085048D && version_check2 == 0x159CF8 {
-            Ok(Some((Version::V120NV, Publisher::EidosFr)))
-        } else if version_check1 == 0xA885048D && version_check2 == 0x159C48 {
-            Ok(Some((Version::V120, Publisher::EidosDe)))
-        } else if version_check1 == 0xA885048D && version_check2 == 0x159DF8 {
-            Ok(Some((Version::V120NV, Publisher::EidosDe)))
-        } else if version_check1 == 0x8085048D && version_check2 == 0x159C38 {
-            Ok(Some((Version::V120, Publisher::EidosSp)))
-        } else if version_check1 == 0x8085048D && version_check2 == 0x159DE8 {
-            Ok(Some((Version::V120NV, Publisher::EidosSp)))
-        } else if version_check1 == 0xB885048D && version_check2 == 0x159BC8 {
-            Ok(Some((Version::V120, Publisher::EidosIt)))
-        } else if version_check1 == 0xB885048D && version_check2 == 0x159D78 {
-            Ok(Some((Version::V120NV, Publisher::EidosIt)))
-        } else if version_check1 == 0x2885048D && version_check2 == 0x159598 {
-            Ok(Some((Version::V120, Publisher::EidosUk)))
-        } else if version_check1 == 0x2885048D && version_check2 == 0x159748 {
-            Ok(Some((Version::V120NV, Publisher::EidosUk)))
-        } else if version_check1 == 0x1B6E9CC && version_check2 == 0x7C8DFFC9 {
-            f.seek(SeekFrom::Start(0x1010))?;
-            f.read_exact(&mut bytes)?;
-            let version_check3 = u32::from_le_bytes(bytes);
-
-            if version_check3 == 0x24AC {
-                Ok(Some((Version::V120NV, Publisher::EaJp)))
-            } else {
-                Ok(Some((Version::V120, Publisher::EaJp)))
+    /// The Remastered edition has no language file, the game follows the language set in Steam.
+    /// None when the manifest has no language, or one the game does not have
+    pub fn get_remastered_edition_lang(
+        app_manifest: Option<&steam::AppManifest>,
+    ) -> Option<String> {
+        let steam_language = app_manifest?.language.as_deref()?;
+        let language = match steam_language.to_ascii_lowercase().as_str() {
+            "english" => "EN",
+            "french" => "FR",
+            "german" => "DE",
+            "italian" => "IT",
+            "spanish" => "ES",
+            "japanese" => "JP",
+            _ => {
+                warn!("Unknown Steam language \"{}\"", steam_language);
+                return None;
             }
-        } else {
-            Ok(None)
-        }
+        };
+        Some(String::from(language))
+    }
+
+    /// The patch level and publisher of a 2000 release executable, see `fingerprint`
+    pub fn get_version_from_exe(exe_path: &Path) -> std::io::Result<Option<(Version, Publisher)>> {
+        Ok(fingerprint::identify(exe_path)?.and_then(|fingerprint| {
+            Some((fingerprint.version.clone()?, fingerprint.publisher.clone()?))
+        }))
     }
 
     #[cfg(windows)]
@@ -317,7 +467,10 @@ impl Installation {
                 r"SOFTWARE\\Square Soft, Inc\\Final Fantasy VIII\\1.00",
                 r"AppPath",
             ) {
-                Ok(app_path) => return Self::from_directory(app_path, Edition::Standard),
+                Ok(app_path) => {
+                    return Self::from_directory(app_path, Edition::Standard)
+                        .map(|installation| installation.with_source(InstallationSource::Registry))
+                }
                 Err(_) => continue,
             }
         }
@@ -379,24 +532,31 @@ impl Installation {
 
     #[cfg(all(feature = "network", feature = "zip"))]
     pub fn install_patch_remote(
-        url: &str,
-        target_dir: &PathBuf,
+        remote: &provision::RemoteFile,
+        target_dir: &Path,
         env: &Env,
+        progress: &dyn Fn(provision::Progress),
     ) -> Result<(), provision::Error> {
-        provision::download_zip(url, "FF8-patch.zip", target_dir, env)
+        provision::download_zip(remote, target_dir, env, progress)
     }
 
     #[cfg(feature = "zip")]
     pub fn install_patch_local(
-        source_file: &PathBuf,
-        target_dir: &PathBuf,
+        source_file: &Path,
+        target_dir: &Path,
+        progress: &dyn Fn(provision::Progress),
     ) -> Result<(), zip::result::ZipError> {
-        provision::extract_zip(source_file, target_dir)
+        provision::extract_zip(source_file, target_dir, progress)
     }
 
     #[cfg(feature = "pe")]
-    pub fn replace_launcher(self: &Installation, env: &Env) -> std::io::Result<()> {
-        match self.replace_launcher_from_app_path(env) {
+    /// The files are copied with `copy`, the elevated process copies them itself
+    pub fn replace_launcher(
+        self: &Installation,
+        env: &Env,
+        copy: &mut dyn FnMut(&Path, &Path) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        match self.replace_launcher_from_app_path(env, copy) {
             Ok(o) => Ok(o),
             Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
                 if cfg!(windows) {
@@ -417,8 +577,25 @@ impl Installation {
         }
     }
 
+    /// The original launcher, kept by `replace_launcher`
+    pub fn get_launcher_backup_path(&self) -> PathBuf {
+        let launcher_path = self.get_launcher_path();
+        self.app_path.join(format!(
+            "{}_Original.exe",
+            launcher_path
+                .with_extension("")
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+        ))
+    }
+
     #[cfg(feature = "pe")]
-    pub fn replace_launcher_from_app_path(&self, env: &Env) -> std::io::Result<()> {
+    pub fn replace_launcher_from_app_path(
+        &self,
+        env: &Env,
+        copy: &mut dyn FnMut(&Path, &Path) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
         let launcher_path = self.get_launcher_path();
         let resource_launcher_path = env.get_resource_launcher_path();
         let (launcher_product_name, legal_copyright) =
@@ -434,23 +611,16 @@ impl Installation {
             launcher_product_name,
             launcher_path.to_string_lossy()
         );
-        let backup_path = self.app_path.join(format!(
-            "{}_Original.exe",
-            launcher_path
-                .with_extension("")
-                .file_name()
-                .unwrap()
-                .to_string_lossy()
-        ));
+        let backup_path = self.get_launcher_backup_path();
         if !backup_path.exists() || launcher_product_name == "FINAL FANTASY VIII for PC" {
-            provision::copy_file(&launcher_path, &backup_path)?
+            copy(&launcher_path, &backup_path)?
         }
         if legal_copyright
             != pe_format::pe_version_info(&resource_launcher_path)
                 .map(|infos| infos.legal_copyright.unwrap_or_default())
                 .unwrap_or_default()
         {
-            provision::copy_file(&resource_launcher_path, &launcher_path)?
+            copy(&resource_launcher_path, &launcher_path)?
         }
 
         Ok(())
@@ -459,10 +629,10 @@ impl Installation {
     pub fn launch_game_via_steam(
         &self,
         ff8_path: &Path,
-        steam_exe: &Path,
         current_dir: &Path,
     ) -> Result<Child, std::io::Error> {
         let app_id = self.get_app_id();
+        let steam_exe = self.steam_exe();
         info!(
             "Launch \"{} -applaunch {} '{}'\" in dir \"{}\"...",
             steam_exe.to_string_lossy(),
@@ -470,7 +640,7 @@ impl Installation {
             ff8_path.to_string_lossy(),
             current_dir.to_string_lossy()
         );
-        run_helper(&mut Command::new(steam_exe))
+        run_helper(&mut Command::new(&steam_exe))
             .args(["-applaunch", app_id.to_string().as_str()])
             .arg(ff8_path.as_os_str())
             .arg("--debug")
@@ -505,13 +675,13 @@ impl Installation {
         }
     }
 
-    pub fn launch_cw(&self, steam_exe: &Path) -> std::io::Result<()> {
+    pub fn launch_cw(&self) -> std::io::Result<()> {
         let cw_path = self.get_cw_path();
 
         if let Err(e) = match self.edition {
             Edition::Standard => Self::launch_game_directly(&cw_path, &self.app_path),
             Edition::Steam | Edition::Remastered => self
-                .launch_game_via_steam(&cw_path, steam_exe, &self.app_path)
+                .launch_game_via_steam(&cw_path, &self.app_path)
                 .or_else(|_| Self::launch_game_directly(&cw_path, &self.app_path)),
         } {
             error!("Unable to launch game: {}", e);
@@ -521,3 +691,20 @@ impl Installation {
         }
     }
 }
+
+/// Several sources can lead to the same installation, the first one is kept
+fn push_installation(installations: &mut Vec<Installation>, installation: Installation) {
+    if !installations
+        .iter()
+        .any(|found| same_path(&found.app_path, &installation.app_path))
+    {
+        installations.push(installation)
+    }
+}
+
+pub(crate) fn same_path(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
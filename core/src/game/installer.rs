@@ -0,0 +1,673 @@
+use crate::config::{Config, UpdateChannel};
+use crate::game::env::Env;
+use crate::game::ffnx_config::FfnxConfig;
+use crate::game::ffnx_installation::{self, FfnxInstallation};
+use crate::game::input_config::InputConfig;
+use crate::game::install_journal::InstallJournal;
+use crate::game::install_manifest::{InstallManifest, ManifestEntry};
+use crate::game::installation::{Edition, Feature, Installation, Publisher, Version};
+use crate::github::GitHubRelease;
+use crate::provision::{Progress, RemoteFile};
+use crate::release_source::ReleaseSource;
+use crate::{github, pe_format, provision, toml};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum InstallError {
+    #[error("Install error: {0}")]
+    ProvisionError(#[from] provision::Error),
+    #[error("Install error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("4GB patch Error: {0}")]
+    PeFormatError(#[from] pe_format::Error),
+    #[error("Configure Error: {0}")]
+    TomlFileError(#[from] toml::FileError),
+    #[error("Invalid FFNx installation")]
+    InvalidFfnxInstallation,
+    #[error("No previous FFNx version to restore")]
+    NoBackup,
+    #[error("Nothing recorded to uninstall")]
+    NoInstallManifest,
+    #[error("No release source to find FFNx releases")]
+    NoReleaseSource,
+    #[error("No FFNx {0} release for this edition of the game")]
+    ReleaseNotFound(String),
+    #[error("FFNx does not support the {0:?} edition of the game")]
+    UnsupportedEdition(Edition),
+}
+
+impl InstallError {
+    /// The kind of the I/O error behind this error, also when it comes from an archive
+    pub fn io_error_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            InstallError::IOError(e)
+            | InstallError::ProvisionError(provision::Error::IoError(e))
+            | InstallError::ProvisionError(provision::Error::ZipError(
+                zip::result::ZipError::Io(e),
+            )) => Some(e.kind()),
+            _ => None,
+        }
+    }
+}
+
+/// The FFNx release to install: the last one of an update channel, a pinned tag,
+/// a release already found, or an archive provided by the user
+#[derive(Debug, Clone)]
+pub enum FfnxRelease {
+    Channel(UpdateChannel),
+    Tag(String),
+    /// Installs the release the user confirmed, even if a newer one was published since
+    Resolved(GitHubRelease),
+    LocalZip(PathBuf),
+}
+
+enum FfnxSource {
+    Remote(RemoteFile),
+    LocalZip(PathBuf),
+}
+
+impl FfnxRelease {
+    pub fn new(update_channel: UpdateChannel, pinned_version: Option<String>) -> Self {
+        match pinned_version {
+            Some(tag) => FfnxRelease::Tag(tag),
+            None => FfnxRelease::Channel(update_channel),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.update_channel().unwrap_or(UpdateChannel::Stable),
+            config.ffnx_version().unwrap_or_default(),
+        )
+    }
+}
+
+/// The installed FFNx compared with the release `Installer::upgrade` would install
+#[derive(Clone)]
+pub enum UpdateStatus {
+    UpToDate,
+    UpdateAvailable(GitHubRelease),
+    /// Development builds cannot be compared with releases
+    DevBuild,
+}
+
+/// The release notes of what `Installer::upgrade` would install
+pub enum Changelog {
+    UpToDate,
+    /// The releases since the installed version, newest first,
+    /// empty when FFNx is installed from a local archive
+    Upgrade(Vec<GitHubRelease>),
+    /// A pinned release older than the installed version
+    Downgrade(GitHubRelease),
+}
+
+impl Changelog {
+    /// The release `upgrade` would install
+    pub fn target(&self) -> Option<&GitHubRelease> {
+        match self {
+            Changelog::UpToDate => None,
+            Changelog::Upgrade(releases) => releases.first(),
+            Changelog::Downgrade(release) => Some(release),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum InstallEvent {
+    CheckingUpdate,
+    InstallingFfnx,
+    UpgradingFfnx,
+    RollingBackFfnx,
+    Uninstalling,
+    FfnxFound(String),
+    UpToDate(String),
+    ConfiguringFfnx,
+    ReplacingLauncher,
+    CopyingGameFiles,
+    InstallingPatch,
+    PatchingExe,
+    Progress(Progress),
+    Finished,
+}
+
+impl fmt::Display for InstallEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstallEvent::CheckingUpdate => write!(f, "Check for FFNx update..."),
+            InstallEvent::InstallingFfnx => write!(f, "Installing FFNx..."),
+            InstallEvent::UpgradingFfnx => write!(f, "Upgrading FFNx..."),
+            InstallEvent::RollingBackFfnx => write!(f, "Restoring the previous FFNx version..."),
+            InstallEvent::Uninstalling => write!(f, "Restoring the game files..."),
+            InstallEvent::FfnxFound(version) => write!(f, "Found FFNx version {}", version),
+            InstallEvent::UpToDate(version) => write!(f, "FFNx {} is up to date", version),
+            InstallEvent::ConfiguringFfnx => write!(f, "Configuring FFNx..."),
+            InstallEvent::ReplacingLauncher => write!(f, "Replacing the game launcher..."),
+            InstallEvent::CopyingGameFiles => write!(f, "Copying game files..."),
+            InstallEvent::InstallingPatch => write!(f, "Installing the 1.02 patch..."),
+            InstallEvent::PatchingExe => write!(f, "Applying the 4GB patch..."),
+            InstallEvent::Progress(progress) => progress.fmt(f),
+            InstallEvent::Finished => write!(f, "Done"),
+        }
+    }
+}
+
+/// Receives the progress of an `Installer`, to report it to the user
+pub trait InstallListener {
+    fn on_event(&self, event: InstallEvent);
+}
+
+impl<F: Fn(InstallEvent)> InstallListener for F {
+    fn on_event(&self, event: InstallEvent) {
+        self(event)
+    }
+}
+
+/// Installs and upgrades FFNx for a game installation, with the same steps for every frontend
+pub struct Installer<'a> {
+    env: &'a Env,
+    source: Option<&'a dyn ReleaseSource>,
+    listener: &'a dyn InstallListener,
+}
+
+impl<'a> Installer<'a> {
+    /// Enough to roll back or uninstall, `with_source` is needed to find releases
+    pub fn new(env: &'a Env, listener: &'a dyn InstallListener) -> Self {
+        Self {
+            env,
+            source: None,
+            listener,
+        }
+    }
+
+    pub fn with_source(mut self, source: &'a dyn ReleaseSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    fn source(&self) -> Result<&'a dyn ReleaseSource, InstallError> {
+        self.source.ok_or(InstallError::NoReleaseSource)
+    }
+
+    fn notify(&self, event: InstallEvent) {
+        if !matches!(event, InstallEvent::Progress(_)) {
+            info!("{}", event);
+        }
+        self.listener.on_event(event)
+    }
+
+    fn notify_progress(&self, progress: Progress) {
+        self.notify(InstallEvent::Progress(progress))
+    }
+
+    /// Extracts the new version aside, then swaps it in, keeping the replaced files for `rollback`.
+    /// Returns false when FFNx is already up to date, unless `force` is set
+    pub fn upgrade(
+        &self,
+        ffnx_installation: &FfnxInstallation,
+        installation: &Installation,
+        release: &FfnxRelease,
+        force: bool,
+    ) -> Result<bool, InstallError> {
+        Self::check_edition(installation)?;
+        // The releases are fetched once, to compare and to download
+        let target = match release {
+            FfnxRelease::LocalZip(_) => None,
+            _ => {
+                self.notify(InstallEvent::CheckingUpdate);
+                self.target_release(release)?
+            }
+        };
+        if !force && !matches!(release, FfnxRelease::LocalZip(_)) {
+            if let UpdateStatus::UpToDate =
+                Self::compare_release(ffnx_installation, release, target.clone())
+            {
+                self.notify(InstallEvent::UpToDate(
+                    ffnx_installation.version.to_string(),
+                ));
+                return Ok(false);
+            }
+        }
+        let asset = target.as_ref().and_then(|target| {
+            FfnxInstallation::find_asset_from_github_release(target, &installation.edition)
+                .map(|asset| github::remote_file_from_asset(target, &asset))
+        });
+        let source = match asset {
+            Some(remote) => FfnxSource::Remote(remote),
+            // The fallback archive of the release source
+            None => self.find_ffnx_release(&installation.edition, release)?,
+        };
+        self.notify(InstallEvent::UpgradingFfnx);
+        let staging_dir = ffnx_installation.staging_path();
+        let mut journal = InstallJournal::from_env(self.env)?;
+        journal.begin(&ffnx_installation.path)?;
+        // The files already extracted by an interrupted upgrade are kept
+        if !journal.is_resumed() && staging_dir.exists() {
+            std::fs::remove_dir_all(&staging_dir)?
+        }
+        if !journal.is_swapped(&staging_dir, &ffnx_installation.path) {
+            let downloaded = self
+                .fetch_ffnx(&source, &staging_dir, &mut journal)
+                .and_then(|()| {
+                    FfnxInstallation::from_directory(&staging_dir, installation)
+                        .map(|_| ())
+                        .ok_or(InstallError::InvalidFfnxInstallation)
+                });
+            if let Err(e) = downloaded {
+                if let Err(e) = std::fs::remove_dir_all(&staging_dir) {
+                    warn!("Cannot remove \"{}\": {}", staging_dir.to_string_lossy(), e)
+                }
+                return Err(e);
+            }
+            // The FFNx.toml of the user replaces the one of the release
+            let config_path = ffnx_installation.config_path();
+            if config_path.exists() {
+                journal.copy_file(&config_path, &staging_dir.join("FFNx.toml"))?
+            }
+        }
+        journal.swap_dir(
+            &staging_dir,
+            &ffnx_installation.path,
+            &ffnx_installation.backup_path(),
+        )?;
+        journal.commit()?;
+        self.notify(InstallEvent::Finished);
+        Ok(true)
+    }
+
+    fn target_release(&self, release: &FfnxRelease) -> Result<Option<GitHubRelease>, InstallError> {
+        Ok(match release {
+            FfnxRelease::Channel(update_channel) => {
+                self.source()?.latest_releases().map(|last_release| {
+                    FfnxInstallation::channel_release(last_release, update_channel.clone())
+                })?
+            }
+            FfnxRelease::Tag(tag) => self.source()?.find_release_by_tag(tag)?,
+            FfnxRelease::Resolved(release) => Some(release.clone()),
+            FfnxRelease::LocalZip(_) => None,
+        })
+    }
+
+    /// The releases `upgrade` would install since the installed version
+    pub fn changelog(
+        &self,
+        ffnx_installation: &FfnxInstallation,
+        release: &FfnxRelease,
+    ) -> Result<Changelog, InstallError> {
+        let Some(target) = self.target_release(release)? else {
+            return Ok(Changelog::Upgrade(Vec::new()));
+        };
+        if ffnx_installation.is_dev_build() {
+            // Development builds cannot be compared
+            return Ok(Changelog::Upgrade(vec![target]));
+        }
+        Ok(match target.version() {
+            Some(version) if version == ffnx_installation.version => Changelog::UpToDate,
+            Some(version)
+                if version < ffnx_installation.version
+                    && matches!(release, FfnxRelease::Tag(_) | FfnxRelease::Resolved(_)) =>
+            {
+                Changelog::Downgrade(target)
+            }
+            _ => match github::releases_between(
+                self.source()?.list_releases()?,
+                &ffnx_installation.version,
+                &target,
+            ) {
+                releases if releases.is_empty() => Changelog::UpToDate,
+                releases => Changelog::Upgrade(releases),
+            },
+        })
+    }
+
+    /// Compares the installed version with the one `upgrade` would install
+    pub fn update_status(
+        &self,
+        ffnx_installation: &FfnxInstallation,
+        release: &FfnxRelease,
+    ) -> Result<UpdateStatus, InstallError> {
+        if ffnx_installation.is_dev_build() {
+            // Not worth fetching the releases
+            return Ok(UpdateStatus::DevBuild);
+        }
+        Ok(Self::compare_release(
+            ffnx_installation,
+            release,
+            self.target_release(release)?,
+        ))
+    }
+
+    fn compare_release(
+        ffnx_installation: &FfnxInstallation,
+        release: &FfnxRelease,
+        target: Option<GitHubRelease>,
+    ) -> UpdateStatus {
+        if ffnx_installation.is_dev_build() {
+            return UpdateStatus::DevBuild;
+        }
+        let Some(target) = target else {
+            return UpdateStatus::UpToDate;
+        };
+        let up_to_date = match (target.version(), release) {
+            // A pinned or confirmed version can be older than the installed one
+            (Some(version), FfnxRelease::Tag(_) | FfnxRelease::Resolved(_)) => {
+                version == ffnx_installation.version
+            }
+            (Some(version), _) => version <= ffnx_installation.version,
+            (None, _) => false,
+        };
+        if up_to_date {
+            UpdateStatus::UpToDate
+        } else {
+            UpdateStatus::UpdateAvailable(target)
+        }
+    }
+
+    /// Restores the FFNx files replaced by the last upgrade
+    pub fn rollback(&self, ffnx_installation: &FfnxInstallation) -> Result<(), InstallError> {
+        if !ffnx_installation.has_backup() {
+            return Err(InstallError::NoBackup);
+        }
+        self.notify(InstallEvent::RollingBackFfnx);
+        provision::restore_backup(&ffnx_installation.path, &ffnx_installation.backup_path())?;
+        self.notify(InstallEvent::Finished);
+        Ok(())
+    }
+
+    /// The steps are written to the install journal, an interrupted install is resumed.
+    /// Every file created or replaced is recorded in the manifest, for `uninstall`
+    pub fn install(
+        &self,
+        ffnx_dir: &Path,
+        installation: &Installation,
+        release: &FfnxRelease,
+    ) -> Result<FfnxInstallation, InstallError> {
+        Self::check_edition(installation)?;
+        let mut journal = InstallJournal::from_env(self.env)?;
+        journal.begin(ffnx_dir)?;
+        let ffnx_installation = self.install_ffnx(ffnx_dir, installation, release, &mut journal)?;
+        self.configure_ffnx(&ffnx_installation)?;
+        if installation.edition.supports(Feature::ReplaceLauncher) {
+            self.notify(InstallEvent::ReplacingLauncher);
+            installation.replace_launcher(self.env, &mut |source, target| {
+                journal.copy_file(source, target)
+            })?;
+            let backup = installation.get_launcher_backup_path();
+            if backup.exists() {
+                journal.manifest()?.record(ManifestEntry::Replaced {
+                    path: installation.get_launcher_path(),
+                    backup,
+                })?
+            }
+        };
+        self.notify(InstallEvent::CopyingGameFiles);
+        self.copy_exe(&ffnx_installation, installation, &mut journal)?;
+        self.install_bink(&ffnx_installation, installation, &mut journal)?;
+        self.install_eax(&ffnx_installation, installation, &mut journal)?;
+        self.install_input_config(&ffnx_installation, installation, &mut journal)?;
+        self.notify(InstallEvent::PatchingExe);
+        let exe_path = ffnx_installation.exe_path();
+        // Also the exe of the game itself, when FFNx is installed in the game directory.
+        // Recorded first, reversing a patch not applied yet does nothing
+        journal
+            .manifest()?
+            .record(ManifestEntry::Patched(exe_path.clone()))?;
+        journal.patch_file(&exe_path, pe_format::pe_patch_4bg)?;
+        journal.commit()?;
+        self.notify(InstallEvent::Finished);
+        Ok(ffnx_installation)
+    }
+
+    /// Puts the game back as it was before `install`, from what it recorded
+    pub fn uninstall(&self, ffnx_dir: &Path) -> Result<(), InstallError> {
+        let manifest = InstallManifest::open(ffnx_dir)?;
+        if manifest.is_empty() {
+            return Err(InstallError::NoInstallManifest);
+        }
+        self.notify(InstallEvent::Uninstalling);
+        manifest.undo()?;
+        self.notify(InstallEvent::Finished);
+        Ok(())
+    }
+
+    fn check_edition(installation: &Installation) -> Result<(), InstallError> {
+        if installation.edition.supports(Feature::Ffnx) {
+            Ok(())
+        } else {
+            Err(InstallError::UnsupportedEdition(
+                installation.edition.clone(),
+            ))
+        }
+    }
+
+    fn install_ffnx(
+        &self,
+        ffnx_dir: &Path,
+        installation: &Installation,
+        release: &FfnxRelease,
+        journal: &mut InstallJournal,
+    ) -> Result<FfnxInstallation, InstallError> {
+        if let Some(ffnx_installation) = FfnxInstallation::from_directory(ffnx_dir, installation) {
+            self.notify(InstallEvent::FfnxFound(
+                if ffnx_installation.is_dev_build() {
+                    String::from("dev")
+                } else {
+                    ffnx_installation.version.to_string()
+                },
+            ));
+            return Ok(ffnx_installation);
+        }
+
+        let source = self.find_ffnx_release(&installation.edition, release)?;
+        self.notify(InstallEvent::InstallingFfnx);
+        // Downloaded or not, the archive is extracted aside then swapped in,
+        // the replaced game files are kept to be restored on uninstall
+        let staging_dir = provision::sibling_dir(ffnx_dir, ffnx_installation::STAGING_DIR);
+        let backup_dir = InstallManifest::original_files_path(ffnx_dir);
+        if !journal.is_resumed() && staging_dir.exists() {
+            std::fs::remove_dir_all(&staging_dir)?
+        }
+        self.fetch_ffnx(&source, &staging_dir, journal)?;
+        journal.swap_dir(&staging_dir, ffnx_dir, &backup_dir)?;
+        let manifest = journal.manifest()?;
+        if manifest.created_dir(ffnx_dir) {
+            std::fs::remove_dir_all(&backup_dir)?
+        } else {
+            manifest.record(ManifestEntry::Swapped {
+                target_dir: ffnx_dir.to_path_buf(),
+                backup_dir,
+            })?
+        }
+        FfnxInstallation::from_directory(ffnx_dir, installation)
+            .ok_or(InstallError::InvalidFfnxInstallation)
+    }
+
+    fn find_ffnx_release(
+        &self,
+        edition: &Edition,
+        release: &FfnxRelease,
+    ) -> Result<FfnxSource, InstallError> {
+        match release {
+            FfnxRelease::Channel(update_channel) => {
+                self.notify(InstallEvent::CheckingUpdate);
+                FfnxInstallation::find_version(self.source()?, edition, update_channel.clone())?
+                    .map(FfnxSource::Remote)
+                    .ok_or_else(|| InstallError::ReleaseNotFound(format!("{:?}", update_channel)))
+            }
+            FfnxRelease::Tag(tag) => {
+                info!("Use pinned FFNx version {}", tag);
+                self.notify(InstallEvent::CheckingUpdate);
+                let release = self
+                    .source()?
+                    .find_release_by_tag(tag)?
+                    .ok_or_else(|| InstallError::ReleaseNotFound(tag.clone()))?;
+                FfnxInstallation::find_asset_from_github_release(&release, edition)
+                    .map(|asset| {
+                        FfnxSource::Remote(github::remote_file_from_asset(&release, &asset))
+                    })
+                    .ok_or_else(|| InstallError::ReleaseNotFound(tag.clone()))
+            }
+            FfnxRelease::Resolved(release) => {
+                FfnxInstallation::find_asset_from_github_release(release, edition)
+                    .map(|asset| {
+                        FfnxSource::Remote(github::remote_file_from_asset(release, &asset))
+                    })
+                    .ok_or_else(|| InstallError::ReleaseNotFound(release.tag_name.clone()))
+            }
+            FfnxRelease::LocalZip(source_file) => Ok(FfnxSource::LocalZip(source_file.clone())),
+        }
+    }
+
+    fn fetch_ffnx(
+        &self,
+        source: &FfnxSource,
+        target_dir: &Path,
+        journal: &mut InstallJournal,
+    ) -> Result<(), InstallError> {
+        let progress = |progress| self.notify_progress(progress);
+        match source {
+            FfnxSource::Remote(remote) => {
+                journal.download_zip(remote, target_dir, self.env, &progress)?
+            }
+            FfnxSource::LocalZip(source_file) => journal
+                .extract_zip(source_file, target_dir, &progress)
+                .map_err(provision::Error::from)?,
+        }
+        Ok(())
+    }
+
+    fn configure_ffnx(&self, ffnx_installation: &FfnxInstallation) -> Result<(), InstallError> {
+        self.notify(InstallEvent::ConfiguringFfnx);
+        let config_path = ffnx_installation.config_path();
+        let mut ffnx_config = FfnxConfig::from_file(&config_path).unwrap_or_default();
+        ffnx_config.set_bool("show_fps", false);
+        ffnx_config.set_bool("show_renderer_backend", false);
+        ffnx_config.set_bool("show_stats", false);
+        ffnx_config.set_bool("show_version", false);
+        Ok(ffnx_config.save(&config_path)?)
+    }
+
+    fn copy_exe(
+        &self,
+        ffnx_installation: &FfnxInstallation,
+        installation: &Installation,
+        journal: &mut InstallJournal,
+    ) -> Result<(), InstallError> {
+        let exe_path = ffnx_installation.exe_path();
+        if !exe_path.exists() {
+            journal.copy_file(&installation.exe_path(), &exe_path)?
+        }
+        Ok(())
+    }
+
+    fn install_bink(
+        &self,
+        ffnx_installation: &FfnxInstallation,
+        installation: &Installation,
+        journal: &mut InstallJournal,
+    ) -> Result<(), InstallError> {
+        let bink_dll_path = ffnx_installation.path.join("binkw32.dll");
+        if bink_dll_path.exists() {
+            return Ok(());
+        }
+        if !matches!(installation.edition, Edition::Standard) {
+            journal.copy_file(&installation.app_path.join("binkw32.dll"), &bink_dll_path)?;
+            return Ok(());
+        }
+        let file_name = match &installation.version {
+            Some((Version::V100, publisher)) => match publisher {
+                Publisher::EaJp => Some("FF8EasqPatch"),
+                Publisher::EaUs | Publisher::EidosUk => Some("FF8SqeaPatch"),
+                Publisher::EidosDe => Some("FF8EidosGerV12"),
+                Publisher::EidosFr => Some("FF8EidosFre"),
+                Publisher::EidosIt => Some("ff8ngita"),
+                Publisher::EidosSp => Some("ff8ngspa"),
+            },
+            // The game is already patched
+            Some(_) => return Ok(()),
+            None => None,
+        };
+        match file_name {
+            Some(file_name) => {
+                self.notify(InstallEvent::InstallingPatch);
+                let url = format!("https://www.ff8.fr/download/programs/{}.zip", file_name);
+                match journal.download_zip(
+                    &provision::RemoteFile::new(url),
+                    &ffnx_installation.path,
+                    self.env,
+                    &|progress| self.notify_progress(progress),
+                ) {
+                    Ok(()) => journal.rename_file(
+                        &ffnx_installation.path.join("FF8.exe"),
+                        &ffnx_installation.exe_path(),
+                    )?,
+                    Err(e) => {
+                        error!("Cannot download 1.02 patch: {}", e)
+                    }
+                }
+            }
+            None => {
+                error!("Cannot detect the version and the language of your game")
+            }
+        }
+        Ok(())
+    }
+
+    fn install_eax(
+        &self,
+        ffnx_installation: &FfnxInstallation,
+        installation: &Installation,
+        journal: &mut InstallJournal,
+    ) -> Result<(), InstallError> {
+        match installation.edition {
+            Edition::Steam => {
+                let eax_dll_path = ffnx_installation.path.join("eax.dll");
+                if !eax_dll_path.exists()
+                    || pe_format::pe_version_info(&eax_dll_path)?
+                        .product_name
+                        .unwrap_or_default()
+                        == "FFNx"
+                {
+                    journal.copy_file(&self.env.mumba_dir.join("eax.dll"), &eax_dll_path)?
+                }
+            }
+            Edition::Standard | Edition::Remastered => {
+                let eax_dll_path = ffnx_installation.path.join("creative_eax.dll");
+                if !eax_dll_path.exists() {
+                    if let Err(e) =
+                        journal.copy_file(&self.env.mumba_dir.join("eax.dll"), &eax_dll_path)
+                    {
+                        warn!("Cannot install creative_eax.dll: {}", e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn install_input_config(
+        &self,
+        ffnx_installation: &FfnxInstallation,
+        installation: &Installation,
+        journal: &mut InstallJournal,
+    ) -> Result<(), InstallError> {
+        if !matches!(&installation.edition, Edition::Standard) {
+            return Ok(());
+        }
+        let ff8_input = ffnx_installation.path.join("override").join("ff8input.cfg");
+        if !ff8_input.exists() {
+            std::fs::create_dir_all(ffnx_installation.path.join("override"))?;
+            journal
+                .copy_file(&installation.app_path.join("ff8input.cfg"), &ff8_input)
+                .or_else(|e| {
+                    warn!(
+                        "Error when copying ff8input.cfg, creating a new one instead: {}",
+                        e
+                    );
+                    InputConfig::new(&installation.edition).to_file(&ff8_input)
+                })?
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,19 @@
+pub mod discovery;
 pub mod env;
 #[cfg(feature = "config")]
 pub mod ffnx_config;
 pub mod ffnx_installation;
+pub mod fingerprint;
 pub mod input_config;
+#[cfg(all(feature = "network", feature = "zip"))]
+pub mod install_journal;
+pub mod install_manifest;
 pub mod installation;
+#[cfg(all(
+    feature = "network",
+    feature = "zip",
+    feature = "pe",
+    feature = "config"
+))]
+pub mod installer;
+pub mod verify;
@@ -0,0 +1,183 @@
+use crate::game::fingerprint::{self, ParseError};
+use crate::game::installation::{self, Edition, Installation, Version};
+#[cfg(feature = "pe")]
+use crate::pe_format;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Shipped with Mumba, see the header of the file for the format
+const GAME_FILES: &str = include_str!("../../data/game_files.txt");
+
+/// DLLs loaded by the game before the ones of Mumba, left by other mods or a manual FFNx install
+const CONFLICTING_FILES: [(&str, &str); 5] = [
+    ("FFNx.dll", "FFNx installed in the game directory"),
+    ("dinput8.dll", "input hook of another mod"),
+    ("d3d9.dll", "graphics mod like Tonberry or ReShade"),
+    ("dxgi.dll", "graphics mod like ReShade"),
+    ("opengl32.dll", "graphics mod like ReShade"),
+];
+
+/// A file of the game directory, as listed in `game_files.txt`
+#[derive(Clone, Debug)]
+pub struct ExpectedFile {
+    pub edition: Edition,
+    pub version: Option<Version>,
+    pub path: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+    Missing(PathBuf),
+    Conflict { path: PathBuf, reason: &'static str },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Missing(path) => write!(f, "Missing: {}", path.to_string_lossy()),
+            Issue::Conflict { path, reason } => {
+                write!(f, "Conflict: {} ({})", path.to_string_lossy(), reason)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub checked_files: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// The files shipped with Mumba
+pub fn game_files() -> &'static [ExpectedFile] {
+    static GAME_FILES_LIST: OnceLock<Vec<ExpectedFile>> = OnceLock::new();
+    GAME_FILES_LIST.get_or_init(|| parse(GAME_FILES).expect("Valid game file list"))
+}
+
+pub fn parse(text: &str) -> Result<Vec<ExpectedFile>, ParseError> {
+    let mut files = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| ParseError {
+            line: index + 1,
+            message,
+        };
+        let mut columns = line.split_whitespace();
+        let (Some(edition), Some(path)) = (columns.next(), columns.next()) else {
+            return Err(error(String::from("Expected edition and path")));
+        };
+        let mut file = ExpectedFile {
+            edition: fingerprint::parse_edition(edition)
+                .ok_or_else(|| error(format!("Unknown edition \"{}\"", edition)))?,
+            version: None,
+            path: String::from(path),
+        };
+        for option in columns {
+            match option.split_once('=') {
+                Some(("version", value)) => {
+                    file.version = Some(
+                        fingerprint::parse_version(value)
+                            .ok_or_else(|| error(format!("Unknown version \"{}\"", value)))?,
+                    )
+                }
+                _ => return Err(error(format!("Unknown option \"{}\"", option))),
+            }
+        }
+        files.push(file)
+    }
+    Ok(files)
+}
+
+/// Checks the game directory with the files shipped with Mumba,
+/// the FFNx directory is skipped from the conflicts when it is the game directory
+pub fn verify(installation: &Installation, ffnx_dir: &Path) -> std::io::Result<Report> {
+    verify_with(game_files(), installation, ffnx_dir)
+}
+
+pub fn verify_with(
+    files: &[ExpectedFile],
+    installation: &Installation,
+    ffnx_dir: &Path,
+) -> std::io::Result<Report> {
+    let mut report = Report::default();
+    let version = installation.version.as_ref().map(|(version, _)| version);
+    for file in files {
+        if file.edition != installation.edition
+            || (file.version.is_some() && file.version.as_ref() != version)
+        {
+            continue;
+        }
+        let path = installation.app_path.join(
+            file.path
+                .replace("{exe}", &installation.exe_name)
+                .replace("{lang}", &installation.language.to_ascii_lowercase()),
+        );
+        report.checked_files += 1;
+        if !path.is_file() && !matches_any_file(&path) {
+            report.issues.push(Issue::Missing(path))
+        }
+    }
+    if !installation::same_path(&installation.app_path, ffnx_dir) {
+        report.issues.extend(find_conflicts(installation))
+    }
+    Ok(report)
+}
+
+/// Whether a file of the directory matches the `*` of the file name
+fn matches_any_file(pattern: &Path) -> bool {
+    let Some((prefix, suffix)) = pattern
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split_once('*'))
+    else {
+        return false;
+    };
+    let Some(Ok(entries)) = pattern.parent().map(std::fs::read_dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+        entry.path().is_file()
+            && name.len() >= prefix.len() + suffix.len()
+            && name.starts_with(&prefix.to_ascii_lowercase())
+            && name.ends_with(&suffix.to_ascii_lowercase())
+    })
+}
+
+fn find_conflicts(installation: &Installation) -> Vec<Issue> {
+    CONFLICTING_FILES
+        .iter()
+        .map(|(file_name, reason)| (installation.app_path.join(file_name), *reason))
+        .filter(|(path, _)| path.is_file())
+        .map(|(path, reason)| Issue::Conflict { path, reason })
+        .chain(find_ffnx_eax(installation))
+        .collect()
+}
+
+/// The Steam release ships its own eax.dll, FFNx replaces it when installed there
+#[cfg(feature = "pe")]
+fn find_ffnx_eax(installation: &Installation) -> Option<Issue> {
+    let eax_path = installation.app_path.join("eax.dll");
+    let is_ffnx = installation.edition == Edition::Steam
+        && pe_format::pe_version_info(&eax_path)
+            .is_ok_and(|info| info.product_name.as_deref() == Some("FFNx"));
+    is_ffnx.then_some(Issue::Conflict {
+        path: eax_path,
+        reason: "FFNx installed in the game directory",
+    })
+}
+
+#[cfg(not(feature = "pe"))]
+fn find_ffnx_eax(_installation: &Installation) -> Option<Issue> {
+    None
+}
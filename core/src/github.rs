@@ -1,38 +1,115 @@
+use crate::http;
 use crate::provision;
+use crate::provision::RemoteFile;
+use crate::version::Version;
 use jiff::Timestamp;
+use regex_lite::Regex;
 use serde::de::{self, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 
 use std::fmt;
 use std::marker::PhantomData;
+use std::sync::LazyLock;
 
-#[derive(Deserialize, Clone)]
+static VERSION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[vV](\d+(?:\.\d+)+)").unwrap());
+
+#[derive(Deserialize, Clone, Debug)]
 pub struct GitHubReleaseAsset {
     pub browser_download_url: String,
     pub name: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// Only filled for recent releases, in the form "sha256:<hex>"
+    #[serde(default)]
+    pub digest: Option<String>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Clone, Debug)]
 pub struct GitHubRelease {
     pub tag_name: String,
     pub id: i64,
     pub assets: Vec<GitHubReleaseAsset>,
     pub prerelease: bool,
     pub draft: bool,
-    pub published_at: String,
+    /// Drafts are not published yet
+    #[serde(default)]
+    pub published_at: Option<String>,
+    /// The release notes, in Markdown
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub html_url: String,
+}
+
+impl GitHubRelease {
+    /// The version from the tag name, or from the asset names when the tag has none (like canary)
+    pub fn version(&self) -> Option<Version> {
+        if let Ok(version) = self.tag_name.parse() {
+            return Some(version);
+        }
+        self.assets.iter().find_map(|asset| {
+            VERSION_REGEX
+                .captures(&asset.name)
+                .and_then(|captures| captures[1].parse().ok())
+        })
+    }
 }
 
+/// FFNx publishes its nightly builds in a single prerelease with this tag
+const CANARY_TAG: &str = "canary";
+
+#[derive(Default)]
 pub struct LatestRelease {
     pub latest: Option<GitHubRelease>,
     pub prerelease: Option<GitHubRelease>,
-    pub latest_not_recent: Option<GitHubRelease>,
+    /// The newest release excluding canary builds, it can be a prerelease
+    pub beta: Option<GitHubRelease>,
+}
+
+impl LatestRelease {
+    pub fn from_releases<I: IntoIterator<Item = GitHubRelease>>(
+        releases: I,
+    ) -> Result<Self, jiff::Error> {
+        let mut latest_release = Self::default();
+        for release in releases {
+            latest_release.add(release)?
+        }
+        Ok(latest_release)
+    }
+
+    fn add(&mut self, release: GitHubRelease) -> Result<(), jiff::Error> {
+        if !release.draft && release.tag_name != CANARY_TAG && is_newer(&release, &self.beta)? {
+            self.beta = Some(release.clone())
+        }
+        if release.prerelease {
+            if is_newer(&release, &self.prerelease)? {
+                self.prerelease = Some(release)
+            }
+        } else if !release.draft && is_newer(&release, &self.latest)? {
+            self.latest = Some(release)
+        }
+        Ok(())
+    }
 }
 
 #[derive(Deserialize)]
 #[serde(transparent)]
-struct GitHubReleases {
+pub struct GitHubReleases {
     #[serde(deserialize_with = "deserialize_max")]
-    latest_release: LatestRelease,
+    pub latest_release: LatestRelease,
+}
+
+fn is_newer(release: &GitHubRelease, current: &Option<GitHubRelease>) -> Result<bool, jiff::Error> {
+    match current {
+        None => Ok(true),
+        Some(current) => Ok(published_at(release)? > published_at(current)?),
+    }
+}
+
+/// A release without date is older than every other one
+fn published_at(release: &GitHubRelease) -> Result<Option<Timestamp>, jiff::Error> {
+    release.published_at.as_deref().map(str::parse).transpose()
 }
 
 fn deserialize_max<'de, D>(deserializer: D) -> Result<LatestRelease, D::Error>
@@ -52,39 +129,15 @@ where
         where
             S: SeqAccess<'de>,
         {
-            let mut latest = None;
-            let mut prerelease = None;
+            let mut latest_release = LatestRelease::default();
 
             while let Some(value) = seq.next_element::<GitHubRelease>()? {
-                if value.prerelease {
-                    prerelease = Some(value)
-                } else if !value.draft {
-                    latest = match &latest {
-                        None => Some(value),
-                        Some(v) => {
-                            let published_at: Timestamp = value
-                                .published_at
-                                .parse()
-                                .map_err(|_| de::Error::custom("Cannot parse date"))?;
-                            let current_published_at: Timestamp = v
-                                .published_at
-                                .parse()
-                                .map_err(|_| de::Error::custom("Cannot parse date"))?;
-                            if published_at > current_published_at {
-                                Some(value)
-                            } else {
-                                latest
-                            }
-                        }
-                    }
-                }
+                latest_release
+                    .add(value)
+                    .map_err(|_| de::Error::custom("Cannot parse date"))?
             }
 
-            Ok(LatestRelease {
-                latest_not_recent: latest.clone(),
-                latest,
-                prerelease,
-            })
+            Ok(latest_release)
         }
     }
 
@@ -98,3 +151,140 @@ pub fn find_last_release(repo_name: &str) -> Result<LatestRelease, provision::Er
     )?;
     Ok(releases.latest_release)
 }
+
+/// Every release, the pages of the GitHub API are followed with their `Link` header
+pub fn list_releases(repo_name: &str) -> Result<Vec<GitHubRelease>, provision::Error> {
+    let mut releases = Vec::new();
+    let mut url = Some(format!(
+        "https://api.github.com/repos/{}/releases?per_page=100",
+        repo_name
+    ));
+    while let Some(page_url) = url {
+        let mut response = http::get(&page_url)?;
+        url = response
+            .headers()
+            .get("link")
+            .and_then(|value| value.to_str().ok())
+            .and_then(next_page_url);
+        releases.extend(response.body_mut().read_json::<Vec<GitHubRelease>>()?)
+    }
+    Ok(releases)
+}
+
+/// The URL of the `rel="next"` page in a `Link` header
+pub fn next_page_url(link: &str) -> Option<String> {
+    link.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"next\"")
+            .then(|| String::from(url.trim().trim_start_matches('<').trim_end_matches('>')))
+    })
+}
+
+pub fn find_release_by_tag(repo_name: &str, tag: &str) -> Result<GitHubRelease, provision::Error> {
+    provision::get_json::<GitHubRelease>(
+        format!(
+            "https://api.github.com/repos/{}/releases/tags/{}",
+            repo_name,
+            encode_path_segment(tag)
+        )
+        .as_str(),
+    )
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986
+pub fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                String::from(byte as char)
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// The releases newer than `installed_version` up to `target`, newest first, to show their notes.
+/// Prereleases are skipped unless `target` is one
+pub fn releases_between(
+    releases: Vec<GitHubRelease>,
+    installed_version: &Version,
+    target: &GitHubRelease,
+) -> Vec<GitHubRelease> {
+    let Some(target_version) = target.version() else {
+        return vec![target.clone()];
+    };
+    if target_version <= *installed_version {
+        return Vec::new();
+    }
+    let mut releases: Vec<(Version, GitHubRelease)> = releases
+        .into_iter()
+        .filter(|release| !release.draft && (target.prerelease || !release.prerelease))
+        .filter(|release| release.tag_name != target.tag_name)
+        .filter_map(|release| Some((release.version()?, release)))
+        .filter(|(version, _)| version > installed_version && *version <= target_version)
+        .collect();
+    releases.push((target_version, target.clone()));
+    releases.sort_by(|(a, _), (b, _)| b.cmp(a));
+    releases.into_iter().map(|(_, release)| release).collect()
+}
+
+/// Checksum files that can be published next to the archives
+const CHECKSUM_FILE_NAMES: [&str; 2] = ["SHA256SUMS", "checksums.txt"];
+
+/// Builds the file to download for an asset, with its size and SHA-256 when GitHub or
+/// the release itself provides them
+pub fn remote_file_from_asset(release: &GitHubRelease, asset: &GitHubReleaseAsset) -> RemoteFile {
+    RemoteFile {
+        url: asset.browser_download_url.clone(),
+        size: asset.size,
+        sha256: find_asset_checksum(release, asset),
+    }
+}
+
+pub fn find_asset_checksum(release: &GitHubRelease, asset: &GitHubReleaseAsset) -> Option<String> {
+    if let Some(sha256) = asset
+        .digest
+        .as_ref()
+        .and_then(|digest| digest.strip_prefix("sha256:"))
+    {
+        return Some(sha256.to_ascii_lowercase());
+    }
+    let sidecar_name = format!("{}.sha256", asset.name);
+    release
+        .assets
+        .iter()
+        .filter(|a| a.name == sidecar_name || CHECKSUM_FILE_NAMES.contains(&a.name.as_str()))
+        .find_map(|checksum_asset| {
+            match provision::get_text(checksum_asset.browser_download_url.as_str()) {
+                Ok(content) => parse_checksum_file(&content, &asset.name),
+                Err(e) => {
+                    warn!("Cannot download {}: {}", checksum_asset.name, e);
+                    None
+                }
+            }
+        })
+}
+
+/// Reads a checksum file in the sha256sum format, a file containing only the hash is accepted too
+pub fn parse_checksum_file(content: &str, file_name: &str) -> Option<String> {
+    let is_sha256 = |hash: &str| hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit());
+    let mut lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    lines
+        .clone()
+        .find_map(|line| {
+            let (hash, name) = line.split_once(char::is_whitespace)?;
+            let name = name.trim_start().trim_start_matches('*');
+            (name == file_name && is_sha256(hash)).then_some(hash)
+        })
+        .or_else(|| match (lines.next(), lines.next()) {
+            (Some(hash), None) if is_sha256(hash) => Some(hash),
+            _ => None,
+        })
+        .map(str::to_ascii_lowercase)
+}
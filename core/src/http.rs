@@ -0,0 +1,100 @@
+use crate::provision::Error;
+use std::sync::OnceLock;
+use std::time::Duration;
+use ureq::http::{Response, StatusCode};
+use ureq::Body;
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+const GITHUB_API_URL: &str = "https://api.github.com/";
+
+static HTTP_CLIENT: OnceLock<HttpClient> = OnceLock::new();
+
+/// HTTP settings, read from the `[http]` table of mumba.toml
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    pub proxy: Option<String>,
+    pub connect_timeout: Option<Duration>,
+    /// Maximum duration of a whole request, download included
+    pub timeout: Option<Duration>,
+    pub user_agent: Option<String>,
+    /// Only sent to the GitHub API, to raise its rate limit
+    pub github_token: Option<String>,
+}
+
+struct HttpClient {
+    agent: ureq::Agent,
+    github_token: Option<String>,
+}
+
+impl HttpClient {
+    fn new(config: &HttpConfig, proxy: Option<ureq::Proxy>) -> Self {
+        let mut builder = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_connect(Some(
+                config.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            ))
+            .timeout_recv_response(Some(DEFAULT_RESPONSE_TIMEOUT))
+            .timeout_global(config.timeout)
+            .user_agent(
+                config
+                    .user_agent
+                    .clone()
+                    .unwrap_or_else(|| format!("mumba/{}", env!("CARGO_PKG_VERSION"))),
+            );
+        // Without explicit proxy, ureq uses the environment variables
+        if proxy.is_some() {
+            builder = builder.proxy(proxy)
+        }
+        Self {
+            agent: ureq::Agent::new_with_config(builder.build()),
+            github_token: config.github_token.clone(),
+        }
+    }
+}
+
+/// Configures the client used by every request, must be called before the first one
+pub fn init(config: &HttpConfig) -> Result<(), Error> {
+    let proxy = config.proxy.as_deref().map(ureq::Proxy::new).transpose()?;
+    if HTTP_CLIENT.set(HttpClient::new(config, proxy)).is_err() {
+        warn!("The HTTP client is already initialized")
+    }
+    Ok(())
+}
+
+fn client() -> &'static HttpClient {
+    HTTP_CLIENT.get_or_init(|| HttpClient::new(&HttpConfig::default(), None))
+}
+
+pub fn get(url: &str) -> Result<Response<Body>, Error> {
+    let client = client();
+    let mut request = client.agent.get(url);
+    if let Some(token) = &client.github_token {
+        if url.starts_with(GITHUB_API_URL) {
+            request = request.header("Authorization", format!("Bearer {}", token))
+        }
+    }
+    check_status(request.call()?)
+}
+
+fn check_status(response: Response<Body>) -> Result<Response<Body>, Error> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    if (status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS)
+        && (header("x-ratelimit-remaining") == Some("0") || header("retry-after").is_some())
+    {
+        let reset = header("x-ratelimit-reset")
+            .and_then(|reset| reset.parse::<i64>().ok())
+            .and_then(|reset| jiff::Timestamp::from_second(reset).ok());
+        return Err(Error::RateLimited(reset));
+    }
+    Err(Error::HttpStatus(status.as_u16()))
+}
@@ -4,19 +4,28 @@ extern crate simplelog;
 
 #[cfg(feature = "config")]
 pub mod config;
+pub mod download_cache;
 pub mod game;
 #[cfg(feature = "network")]
 pub mod github;
+#[cfg(feature = "network")]
+pub mod http;
 #[cfg(feature = "i18n")]
 pub mod i18n;
 #[cfg(feature = "iro")]
 pub mod iro;
+pub mod markdown;
 pub mod mumba_log;
 pub mod os;
 #[cfg(feature = "pe")]
 pub mod pe_format;
 pub mod provision;
+#[cfg(feature = "network")]
+pub mod release_source;
 pub mod screen;
+#[cfg(all(feature = "network", feature = "config"))]
+pub mod self_update;
 pub mod steam;
 #[cfg(feature = "config")]
 pub mod toml;
+pub mod version;
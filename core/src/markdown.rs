@@ -0,0 +1,64 @@
+use regex_lite::Regex;
+use std::sync::LazyLock;
+
+static LINK_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap());
+static CODE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`]+)`").unwrap());
+static BOLD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\*\*([^*\s](?:[^*]*[^*\s])?)\*\*").unwrap());
+/// Underscores inside words, like in `snake__case`, are not emphasis
+static UNDERSCORE_BOLD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^\w])__([^_\s](?:[^_]*[^_\s])?)__([^\w]|$)").unwrap());
+
+/// Renders the Markdown of release notes as plain text: titles, lists, links and emphasis
+/// are kept readable, anything else is left as is
+pub fn to_plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut previous_blank = true;
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        if line.trim_start().starts_with("<!--") {
+            continue;
+        }
+        if line.is_empty() {
+            if !previous_blank {
+                text.push('\n')
+            }
+            previous_blank = true;
+            continue;
+        }
+        previous_blank = false;
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let content = line.trim_start();
+        let content = if let Some(title) = content.strip_prefix('#') {
+            title.trim_start_matches('#').trim_start().to_string()
+        } else if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| content.strip_prefix(bullet))
+        {
+            format!("• {}", item)
+        } else {
+            content.to_string()
+        };
+        let content = LINK_REGEX.replace_all(&content, "$1");
+        text.push_str(indent);
+        // The code is kept as is
+        let mut end = 0;
+        for code in CODE_REGEX.captures_iter(&content) {
+            let code_span = code.get(0).unwrap();
+            text.push_str(&remove_emphasis(&content[end..code_span.start()]));
+            text.push_str(&code[1]);
+            end = code_span.end()
+        }
+        text.push_str(&remove_emphasis(&content[end..]));
+        text.push('\n')
+    }
+    text.trim_end().to_string()
+}
+
+fn remove_emphasis(text: &str) -> String {
+    let text = BOLD_REGEX.replace_all(text, "$1");
+    UNDERSCORE_BOLD_REGEX
+        .replace_all(&text, "${1}${2}${3}")
+        .into_owned()
+}
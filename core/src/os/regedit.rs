@@ -41,6 +41,22 @@ pub fn reg_search_installed_app_by_key(path: &'static str) -> Option<String> {
     None
 }
 
+pub fn reg_key_exists(loc: RegLocation, path: &str) -> bool {
+    reg_open(RegTarget::None, loc, path).is_ok()
+}
+
+/// The names of the subkeys, empty when the key does not exist
+pub fn reg_subkeys(target: RegTarget, loc: RegLocation, path: &str) -> Vec<String> {
+    match reg_open(target, loc, path) {
+        Ok(reg_key) => reg_key
+            .keys()
+            .filter_map(|key| key.ok())
+            .map(|key| key.to_string())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
 fn reg_open<Q>(target: RegTarget, loc: RegLocation, path: Q) -> Result<RegKey, registry::key::Error>
 where
     Q: TryInto<U16CString>,
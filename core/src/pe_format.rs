@@ -1,7 +1,8 @@
+use crate::provision;
 use pelite::pe32::Pe;
 use std::fs::File;
-use std::io::{Seek, SeekFrom, Write};
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,6 +15,8 @@ pub enum Error {
     PeliteFindError(#[from] pelite::resources::FindError),
     #[error("No version available")]
     NoVersion,
+    #[error("Invalid EXE/DLL after patch: {0}")]
+    VerificationFailed(&'static str),
 }
 
 pub struct VersionInfo {
@@ -79,44 +82,179 @@ pub fn pe_version_info<P: AsRef<Path> + ?Sized>(path: &P) -> Result<VersionInfo,
     }
 }
 
+/// Flags of the file header set by a patch, and cleared when it is reversed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PePatch {
+    pub characteristics: u16,
+}
+
+impl PePatch {
+    /// Lets the 32-bit game address up to 4GB of memory
+    pub const LARGE_ADDRESS_AWARE: Self = Self {
+        characteristics: pelite::image::IMAGE_FILE_LARGE_ADDRESS_AWARE,
+    };
+}
+
 pub fn pe_patch_4bg(path: &Path) -> Result<bool, Error> {
-    // Map the file into memory
-    let file_map = pelite::FileMap::open(path)?;
+    pe_patch(path, PePatch::LARGE_ADDRESS_AWARE)
+}
 
-    // Interpret as a PE image
-    let image = pelite::pe32::PeFile::from_bytes(file_map.as_ref())?;
+/// The original file, kept by the first `pe_patch`
+pub fn pe_backup_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".bak");
+    path.with_file_name(file_name)
+}
+
+/// The SHA-256 of the file before and after the last `pe_patch`, one per line,
+/// to tell whether the backup and the file are still the ones of the patch
+pub fn pe_backup_hash_path(path: &Path) -> PathBuf {
+    let mut file_name = pe_backup_path(path).into_os_string();
+    file_name.push(".sha256");
+    PathBuf::from(file_name)
+}
 
-    // Already patched
-    if (image.nt_headers().FileHeader.Characteristics
-        & pelite::image::IMAGE_FILE_LARGE_ADDRESS_AWARE)
-        != 0
+/// Applies the patch with `pe_rewrite`, returns false when it is already applied
+pub fn pe_patch(path: &Path, patch: PePatch) -> Result<bool, Error> {
+    pe_rewrite(
+        path,
+        |characteristics| characteristics | patch.characteristics,
+        true,
+    )
+}
+
+/// Reverses the patch. The backup is put back only when it is still the file before the patch
+/// and the file was not replaced since, like by an update of the game. Otherwise the
+/// characteristics are rewritten and the backup is removed if it matches the result
+pub fn pe_unpatch(path: &Path, patch: PePatch) -> Result<bool, Error> {
+    let backup_path = pe_backup_path(path);
+    let hash_path = pe_backup_hash_path(path);
+    let hashes = match std::fs::read_to_string(&hash_path) {
+        Ok(hashes) => hashes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut hashes = hashes.lines().map(str::trim);
+    if let (Some(original_hash), Some(patched_hash)) = (hashes.next(), hashes.next()) {
+        let hash = provision::sha256_file(path)?;
+        if hash == patched_hash
+            && backup_path.exists()
+            && provision::sha256_file(&backup_path)? == original_hash
+        {
+            info!(
+                "Restore \"{}\" from \"{}\"",
+                path.to_string_lossy(),
+                backup_path.to_string_lossy()
+            );
+            std::fs::rename(&backup_path, path)?;
+            std::fs::remove_file(&hash_path)?;
+            return Ok(true);
+        }
+        if hash != patched_hash && hash != original_hash {
+            warn!(
+                "\"{}\" was replaced since the patch, the backup is not restored",
+                path.to_string_lossy()
+            )
+        }
+    }
+    let reversed = pe_rewrite(
+        path,
+        |characteristics| characteristics & !patch.characteristics,
+        false,
+    )?;
+    if backup_path.exists()
+        && provision::sha256_file(&backup_path)? == provision::sha256_file(path)?
     {
-        return Ok(false);
+        std::fs::remove_file(backup_path)?;
+        if hash_path.exists() {
+            std::fs::remove_file(hash_path)?
+        }
     }
+    Ok(reversed)
+}
 
-    let characteristics_offset = image.dos_header().e_lfanew as u64 + 22;
-    let checksum_offset = image.dos_header().e_lfanew as u64 + 88;
-    let characteristics = image.nt_headers().FileHeader.Characteristics
-        | pelite::image::IMAGE_FILE_LARGE_ADDRESS_AWARE;
+/// Writes the new characteristics and checksum to a temporary file, checks it can be parsed again
+/// with the expected headers, then replaces the original file
+fn pe_rewrite(path: &Path, change: impl Fn(u16) -> u16, keep_backup: bool) -> Result<bool, Error> {
+    // Read as is, a mapped file is padded to the size of a page
+    let mut bytes = std::fs::read(path)?;
+    let image = pelite::pe32::PeFile::from_bytes(&bytes)?;
+    let characteristics = image.nt_headers().FileHeader.Characteristics;
+    let new_characteristics = change(characteristics);
+    if new_characteristics == characteristics {
+        return Ok(false);
+    }
+    let characteristics_offset = image.dos_header().e_lfanew as usize + 22;
+    let checksum_offset = image.dos_header().e_lfanew as usize + 88;
 
-    drop(file_map);
+    bytes[characteristics_offset..characteristics_offset + 2]
+        .copy_from_slice(&new_characteristics.to_le_bytes());
+    let checksum = pe_checksum(&bytes, checksum_offset);
+    bytes[checksum_offset..checksum_offset + 4].copy_from_slice(&checksum.to_le_bytes());
 
-    // Modify file
-    let mut f = File::options().read(false).write(true).open(path)?;
-    f.seek(SeekFrom::Start(characteristics_offset))?;
-    f.write_all(&characteristics.to_le_bytes())?;
-    drop(f);
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let written = write_and_verify(&temp_path, &bytes, new_characteristics);
+    if let Err(e) = written {
+        if let Err(e) = std::fs::remove_file(&temp_path) {
+            warn!("Cannot remove \"{}\": {}", temp_path.to_string_lossy(), e)
+        }
+        return Err(e);
+    }
+    if keep_backup {
+        // The file is not patched yet, it is the one to restore
+        std::fs::copy(path, pe_backup_path(path))?;
+        std::fs::write(
+            pe_backup_hash_path(path),
+            format!(
+                "{}\n{}\n",
+                provision::sha256_file(path)?,
+                provision::sha256_file(&temp_path)?
+            ),
+        )?
+    }
+    info!(
+        "Patch \"{}\", characteristics {:#06x} -> {:#06x}",
+        path.to_string_lossy(),
+        characteristics,
+        new_characteristics
+    );
+    std::fs::rename(&temp_path, path)?;
+    Ok(true)
+}
 
-    let file_map = pelite::FileMap::open(path)?;
-    let image = pelite::pe32::PeFile::from_bytes(file_map.as_ref())?;
-    // Recalculate checksum
-    let checksum = image.headers().check_sum();
-    drop(file_map);
+fn write_and_verify(path: &Path, bytes: &[u8], characteristics: u16) -> Result<(), Error> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    drop(file);
 
-    // Modify file
-    let mut f = File::options().read(false).write(true).open(path)?;
-    f.seek(SeekFrom::Start(checksum_offset))?;
-    f.write_all(&checksum.to_le_bytes())?;
+    let written = std::fs::read(path)?;
+    let image = pelite::pe32::PeFile::from_bytes(&written)?;
+    if written != bytes {
+        return Err(Error::VerificationFailed("content differs"));
+    }
+    if image.nt_headers().FileHeader.Characteristics != characteristics {
+        return Err(Error::VerificationFailed("unexpected characteristics"));
+    }
+    Ok(())
+}
 
-    Ok(true)
+/// The checksum of the optional header, computed like `CheckSumMappedFile`,
+/// the trailing bytes are padded with zeros
+fn pe_checksum(bytes: &[u8], checksum_offset: usize) -> u32 {
+    let mut checksum = 0u64;
+    for (index, dword) in bytes.chunks(4).enumerate() {
+        if index * 4 == checksum_offset {
+            continue;
+        }
+        let mut padded = [0u8; 4];
+        padded[..dword.len()].copy_from_slice(dword);
+        checksum += u32::from_le_bytes(padded) as u64;
+        checksum = (checksum & 0xffff_ffff) + (checksum >> 32);
+    }
+    checksum = (checksum & 0xffff) + (checksum >> 16);
+    checksum = (checksum + (checksum >> 16)) & 0xffff;
+    (checksum + bytes.len() as u64) as u32
 }
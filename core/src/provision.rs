@@ -1,27 +1,153 @@
 #[cfg(feature = "network")]
+use crate::download_cache::DownloadCache;
+#[cfg(feature = "network")]
 use crate::game::env::Env;
+#[cfg(feature = "network")]
+use crate::http;
 use log::info;
 #[cfg(feature = "network")]
 use serde::de::DeserializeOwned;
-#[cfg(feature = "network")]
+use sha2::{Digest, Sha256};
+use std::fmt;
 use std::fs::File;
 #[cfg(feature = "network")]
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
-#[cfg(feature = "zip")]
-use zip_extensions::*;
+
+const BACKUP_FILES_DIR: &str = "files";
+const BACKUP_ADDED_FILES: &str = "added_files.txt";
+
+/// Downloads are capped to avoid filling the disk with an unexpected response
+#[cfg(feature = "network")]
+const MAX_DOWNLOAD_SIZE: u64 = 250_000_000;
 
 #[derive(Error, Debug)]
 pub enum Error {
     #[cfg(feature = "network")]
     #[error("HTTP Error: {0}")]
     HttpError(ureq::Error),
+    #[cfg(feature = "network")]
+    #[error("HTTP Error: status code {0}")]
+    HttpStatus(u16),
+    #[cfg(feature = "network")]
+    #[error(
+        "GitHub API rate limit exceeded{}, set github_token in the [http] section of mumba.toml to raise it",
+        .0.map(|reset| format!(" until {}", reset)).unwrap_or_default()
+    )]
+    RateLimited(Option<jiff::Timestamp>),
+    #[cfg(feature = "network")]
+    #[error("JSON Error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[cfg(feature = "network")]
+    #[error("Date Error: {0}")]
+    DateError(#[from] jiff::Error),
     #[error("I/O Error: {0}")]
     IoError(#[from] std::io::Error),
     #[cfg(feature = "zip")]
     #[error("Zip Error: {0}")]
     ZipError(zip::result::ZipError),
+    #[error("Size mismatch: expected {expected} bytes, got {actual} bytes")]
+    SizeMismatch { expected: u64, actual: u64 },
+    #[error("Checksum mismatch: expected SHA-256 {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("The file is too large (more than {0} bytes)")]
+    TooLarge(u64),
+}
+
+/// A file to download, with its expected size and SHA-256 when the source provides them
+#[derive(Debug, Clone, Default)]
+pub struct RemoteFile {
+    pub url: String,
+    pub size: Option<u64>,
+    pub sha256: Option<String>,
+}
+
+impl RemoteFile {
+    pub fn new<S: Into<String>>(url: S) -> Self {
+        Self {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Progression of a long provisioning task
+#[derive(Debug, Clone, Copy)]
+pub enum Progress {
+    /// Bytes received so far, and the total from Content-Length when the server sends it
+    Download { downloaded: u64, total: Option<u64> },
+    /// Number of zip entries extracted so far
+    Extract { current: usize, total: usize },
+}
+
+impl Progress {
+    /// Ratio between 0.0 and 1.0, if the total is known
+    pub fn ratio(&self) -> Option<f32> {
+        match *self {
+            Progress::Download {
+                downloaded,
+                total: Some(total),
+            } if total > 0 => Some(downloaded as f32 / total as f32),
+            Progress::Download { .. } => None,
+            Progress::Extract { current, total } if total > 0 => {
+                Some(current as f32 / total as f32)
+            }
+            Progress::Extract { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MB: f64 = 1_000_000.0;
+        match *self {
+            Progress::Download {
+                downloaded,
+                total: Some(total),
+            } => write!(
+                f,
+                "Downloading... {:.1} MB / {:.1} MB ({:.0}%)",
+                downloaded as f64 / MB,
+                total as f64 / MB,
+                self.ratio().unwrap_or_default() * 100.0
+            ),
+            Progress::Download {
+                downloaded,
+                total: None,
+            } => write!(f, "Downloading... {:.1} MB", downloaded as f64 / MB),
+            Progress::Extract { current, total } => {
+                write!(f, "Extracting... {}/{} files", current, total)
+            }
+        }
+    }
+}
+
+/// Reports download progression every `REPORT_STEP` bytes
+#[cfg(feature = "network")]
+struct ProgressReader<'a, R> {
+    inner: R,
+    downloaded: u64,
+    last_report: u64,
+    total: Option<u64>,
+    progress: &'a dyn Fn(Progress),
+}
+
+#[cfg(feature = "network")]
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        const REPORT_STEP: u64 = 256 * 1024;
+        let len = self.inner.read(buf)?;
+        self.downloaded += len as u64;
+        if len == 0 || self.downloaded - self.last_report >= REPORT_STEP {
+            self.last_report = self.downloaded;
+            (self.progress)(Progress::Download {
+                downloaded: self.downloaded,
+                total: self.total,
+            })
+        }
+        Ok(len)
+    }
 }
 
 #[cfg(feature = "network")]
@@ -44,54 +170,236 @@ impl From<zip::result::ZipError> for Error {
     }
 }
 
+/// The path of a `file://` URL, so local mirrors can be used like remote ones
+pub fn local_path(url: &str) -> Option<PathBuf> {
+    let path = url.strip_prefix("file://")?;
+    // "file:///C:/FFNx" on Windows
+    let path = match path.strip_prefix('/') {
+        Some(windows_path) if cfg!(windows) && windows_path.get(1..2) == Some(":") => windows_path,
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// The `file://` URL of a local file, the reverse of `local_path`
+pub fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path)
+    }
+}
+
 #[cfg(feature = "network")]
 pub fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, Error> {
-    Ok(ureq::get(url).call()?.body_mut().read_json::<T>()?)
+    if let Some(path) = local_path(url) {
+        return Ok(serde_json::from_reader(std::io::BufReader::new(
+            File::open(path)?,
+        ))?);
+    }
+    Ok(http::get(url)?.body_mut().read_json::<T>()?)
+}
+
+#[cfg(feature = "network")]
+pub fn get_text(url: &str) -> Result<String, Error> {
+    if let Some(path) = local_path(url) {
+        return Ok(std::fs::read_to_string(path)?);
+    }
+    Ok(http::get(url)?.body_mut().read_to_string()?)
+}
+
+/// Returns the path of the file, taken from the cache or downloaded into it
+#[cfg(feature = "network")]
+pub fn download(
+    remote: &RemoteFile,
+    env: &Env,
+    progress: &dyn Fn(Progress),
+) -> Result<PathBuf, Error> {
+    if let Some(path) = local_path(&remote.url) {
+        let size = std::fs::metadata(&path)?.len();
+        verify_file(&path, size, remote)?;
+        return Ok(path);
+    }
+    let cache = DownloadCache::from_env(env);
+    match cache.get(remote) {
+        Some(path) => Ok(path),
+        None => download_to_cache(remote, &cache, progress),
+    }
 }
 
 #[cfg(all(feature = "network", feature = "zip"))]
 pub fn download_zip(
-    url: &str,
-    local_zip_name: &str,
-    target_dir: &PathBuf,
+    remote: &RemoteFile,
+    target_dir: &Path,
     env: &Env,
+    progress: &dyn Fn(Progress),
 ) -> Result<(), Error> {
-    let temp_dir = env.cache_dir.as_path();
-    let archive_path = temp_dir.join(local_zip_name);
+    let archive_path = download(remote, env, progress)?;
     info!(
-        "Download file from \"{}\" to \"{}\"",
-        url,
-        archive_path.to_string_lossy()
+        "Extract file \"{}\" to \"{}\"",
+        archive_path.to_string_lossy(),
+        target_dir.to_string_lossy()
     );
-    let mut response = ureq::get(url).call()?;
-    let mut reader = response.body_mut().as_reader().take(250_000_000);
-    let ret = from_reader(&mut reader, &archive_path, target_dir);
+    extract_zip(&archive_path, target_dir, progress).map_err(|e| {
+        discard_archive(remote, &archive_path, &e);
+        Error::from(e)
+    })
+}
+
+/// Do not keep a corrupt archive for the next attempt, unless it is a local file.
+/// An I/O error, like a target file in use, does not mean the archive is corrupt
+#[cfg(all(feature = "network", feature = "zip"))]
+pub fn discard_archive(remote: &RemoteFile, archive_path: &Path, error: &zip::result::ZipError) {
+    if !matches!(error, zip::result::ZipError::Io(_)) && local_path(&remote.url).is_none() {
+        if let Err(e) = std::fs::remove_file(archive_path) {
+            warn!(
+                "Cannot remove file \"{}\": {}",
+                archive_path.to_string_lossy(),
+                e
+            )
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+fn download_to_cache(
+    remote: &RemoteFile,
+    cache: &DownloadCache,
+    progress: &dyn Fn(Progress),
+) -> Result<PathBuf, Error> {
+    let archive_path = cache.path_for(&remote.url);
+    let part_path = archive_path.with_extension("part");
     info!(
-        "Remove temporary file \"{}\"",
+        "Download file from \"{}\" to \"{}\"",
+        remote.url,
         archive_path.to_string_lossy()
     );
-    match std::fs::remove_file(&archive_path) {
-        Ok(ok) => ok,
-        Err(e) => warn!(
-            "Cannot remove file \"{}\": {}",
-            archive_path.to_string_lossy(),
-            e
-        ),
+    std::fs::create_dir_all(cache.dir())?;
+    let mut response = http::get(&remote.url)?;
+    let total = response.body().content_length();
+    if let (Some(expected), Some(actual)) = (remote.size, total) {
+        if expected != actual {
+            return Err(Error::SizeMismatch { expected, actual });
+        }
+    }
+    let mut reader = ProgressReader {
+        inner: response.body_mut().as_reader().take(MAX_DOWNLOAD_SIZE + 1),
+        downloaded: 0,
+        last_report: 0,
+        total,
+        progress,
     };
-    ret
+    let ret = download_file(&mut reader, &part_path)
+        .and_then(|size| verify_file(&part_path, size, remote))
+        .and_then(|()| Ok(std::fs::rename(&part_path, &archive_path)?));
+    if let Err(e) = ret {
+        if let Err(e) = std::fs::remove_file(&part_path) {
+            warn!(
+                "Cannot remove file \"{}\": {}",
+                part_path.to_string_lossy(),
+                e
+            )
+        }
+        return Err(e);
+    }
+    if let Err(e) = cache.evict() {
+        warn!("Cannot evict old files from the cache: {}", e)
+    }
+    Ok(archive_path)
+}
+
+/// Checks a downloaded file against what the source announced, before using it
+pub fn verify_file(path: &Path, size: u64, remote: &RemoteFile) -> Result<(), Error> {
+    if let Some(expected) = remote.size {
+        if expected != size {
+            return Err(Error::SizeMismatch {
+                expected,
+                actual: size,
+            });
+        }
+    }
+    if let Some(expected) = &remote.sha256 {
+        let actual = sha256_file(path)?;
+        if !expected.eq_ignore_ascii_case(&actual) {
+            return Err(Error::ChecksumMismatch {
+                expected: expected.clone(),
+                actual,
+            });
+        }
+        info!("SHA-256 checksum verified: {}", actual)
+    }
+    Ok(())
+}
+
+pub fn sha256_file(path: &Path) -> Result<String, std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
 }
 
 #[cfg(feature = "zip")]
 pub fn extract_zip(
-    source_file: &PathBuf,
-    target_dir: &PathBuf,
+    source_file: &Path,
+    target_dir: &Path,
+    progress: &dyn Fn(Progress),
 ) -> Result<(), zip::result::ZipError> {
     info!(
         "Extract zip from \"{}\" to \"{}\"...",
         source_file.to_string_lossy(),
         target_dir.to_string_lossy()
     );
-    zip_extract(source_file, target_dir)
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(source_file)?)?;
+    let total = archive.len();
+    for i in 0..total {
+        let mut entry = archive.by_index(i)?;
+        let entry_path = match entry.enclosed_name() {
+            Some(path) => target_dir.join(path),
+            None => {
+                warn!("Ignore zip entry with unsafe path \"{}\"", entry.name());
+                continue;
+            }
+        };
+        if entry.is_dir() {
+            std::fs::create_dir_all(&entry_path)?;
+        } else {
+            if let Some(parent) = entry_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = std::fs::File::create(&entry_path)?;
+            std::io::copy(&mut entry, &mut file)?;
+            // Keeps the executable bit, directories are left writable for their entries
+            #[cfg(unix)]
+            if let Some(mode) = entry.unix_mode() {
+                use std::os::unix::fs::PermissionsExt;
+                file.set_permissions(std::fs::Permissions::from_mode(mode))?
+            }
+        }
+        progress(Progress::Extract {
+            current: i + 1,
+            total,
+        })
+    }
+    Ok(())
+}
+
+/// The files `extract_zip` would write, relative to the target directory
+#[cfg(feature = "zip")]
+pub fn list_zip_files(source_file: &Path) -> Result<Vec<PathBuf>, zip::result::ZipError> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(source_file)?)?;
+    let mut files = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        if let (false, Some(path)) = (entry.is_dir(), entry.enclosed_name()) {
+            files.push(path)
+        }
+    }
+    Ok(files)
 }
 
 pub fn copy_file(source_file: &PathBuf, target_file: &PathBuf) -> Result<(), std::io::Error> {
@@ -112,18 +420,231 @@ pub fn rename_file(source_file: &PathBuf, target_file: &PathBuf) -> Result<(), s
     std::fs::rename(source_file, target_file).and(Ok(()))
 }
 
-#[cfg(all(feature = "network", feature = "zip"))]
-fn from_reader<R: Read + ?Sized>(
-    reader: &mut R,
-    archive_path: &PathBuf,
-    target_dir: &PathBuf,
-) -> Result<(), Error> {
-    let mut file = File::create(archive_path)?;
-    std::io::copy(reader, &mut file)?;
+/// Lists the files of a directory recursively, relative to this directory
+pub fn list_files(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative_dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir.join(&relative_dir))? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                dirs.push(relative_path)
+            } else {
+                files.push(relative_path)
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// A directory next to `dir`, named after it with a suffix, to be renamed in its place
+pub fn sibling_dir(dir: &Path, suffix: &str) -> PathBuf {
+    let mut name = dir.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    dir.with_file_name(name)
+}
+
+/// Renames `staging_dir` to `target_dir`, the previous directory is renamed in `backup_dir`
+/// with the list of added files, so `restore_backup` can undo the swap. The files of the target
+/// missing from the staging directory are moved back in the new directory.
+/// `staging_dir` and `backup_dir` must be outside of `target_dir`, the files are moved one by one
+/// when the directories cannot be renamed, like on another filesystem or when a file is open on
+/// Windows. The target is restored right away on error.
+pub fn swap_dir(
+    staging_dir: &Path,
+    target_dir: &Path,
+    backup_dir: &Path,
+) -> Result<(), std::io::Error> {
+    let files = prepare_swap(staging_dir, target_dir, backup_dir)?;
+    let backup_files_dir = backup_dir.join(BACKUP_FILES_DIR);
+    let swapped = if target_dir.exists() {
+        rename_dirs(staging_dir, target_dir, &backup_files_dir)
+    } else {
+        std::fs::create_dir_all(&backup_files_dir)?;
+        std::fs::rename(staging_dir, target_dir)
+    };
+    match swapped {
+        Ok(()) => {
+            if let Err(e) = move_back_missing_files(&backup_files_dir, target_dir, &files) {
+                error!(
+                    "Cannot move back the files of \"{}\": {}",
+                    target_dir.to_string_lossy(),
+                    e
+                );
+                if let Err(e) = restore_backup(target_dir, backup_dir) {
+                    error!("Cannot restore the backup: {}", e)
+                }
+                return Err(e);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            info!(
+                "Cannot rename the directories ({}), move the files one by one",
+                e
+            );
+            swap_each_file(staging_dir, target_dir, backup_dir, &files)
+        }
+    }
+}
+
+/// The target is left unchanged when the staging directory cannot be renamed
+fn rename_dirs(
+    staging_dir: &Path,
+    target_dir: &Path,
+    backup_files_dir: &Path,
+) -> Result<(), std::io::Error> {
+    std::fs::rename(target_dir, backup_files_dir)?;
+    if let Err(e) = std::fs::rename(staging_dir, target_dir) {
+        std::fs::rename(backup_files_dir, target_dir)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Moves the entries of the previous directory without any new file inside, as a whole
+fn move_back_missing_files(
+    backup_files_dir: &Path,
+    target_dir: &Path,
+    new_files: &[PathBuf],
+) -> Result<(), std::io::Error> {
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative_dir) = dirs.pop() {
+        for entry in std::fs::read_dir(backup_files_dir.join(&relative_dir))? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if !new_files
+                .iter()
+                .any(|file| file.starts_with(&relative_path))
+            {
+                std::fs::rename(entry.path(), target_dir.join(&relative_path))?
+            } else if entry.file_type()?.is_dir() {
+                dirs.push(relative_path)
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Like `swap_dir`, moving the files one by one, for a directory that cannot be renamed
+/// such as the one of the running executable
+pub fn swap_files(
+    staging_dir: &Path,
+    target_dir: &Path,
+    backup_dir: &Path,
+) -> Result<(), std::io::Error> {
+    let files = prepare_swap(staging_dir, target_dir, backup_dir)?;
+    swap_each_file(staging_dir, target_dir, backup_dir, &files)
+}
+
+/// Creates the backup directory with the list of the files `staging_dir` adds to `target_dir`
+fn prepare_swap(
+    staging_dir: &Path,
+    target_dir: &Path,
+    backup_dir: &Path,
+) -> Result<Vec<PathBuf>, std::io::Error> {
     info!(
-        "Extract file \"{}\" to \"{}\"",
-        archive_path.to_string_lossy(),
+        "Swap \"{}\" into \"{}\", backup in \"{}\"",
+        staging_dir.to_string_lossy(),
+        target_dir.to_string_lossy(),
+        backup_dir.to_string_lossy()
+    );
+    if backup_dir.exists() {
+        std::fs::remove_dir_all(backup_dir)?
+    }
+    std::fs::create_dir_all(backup_dir)?;
+    let files = list_files(staging_dir)?;
+    let added_files: String = files
+        .iter()
+        .filter(|file| !target_dir.join(file).exists())
+        .map(|file| format!("{}\n", file.to_string_lossy()))
+        .collect();
+    std::fs::write(backup_dir.join(BACKUP_ADDED_FILES), added_files)?;
+    Ok(files)
+}
+
+fn swap_each_file(
+    staging_dir: &Path,
+    target_dir: &Path,
+    backup_dir: &Path,
+    files: &[PathBuf],
+) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(backup_dir.join(BACKUP_FILES_DIR))?;
+    for file in files {
+        if let Err(e) = swap_file(file, staging_dir, target_dir, backup_dir) {
+            error!("Cannot swap \"{}\": {}", file.to_string_lossy(), e);
+            if let Err(e) = restore_backup(target_dir, backup_dir) {
+                error!("Cannot restore the backup: {}", e)
+            }
+            return Err(e);
+        }
+    }
+    std::fs::remove_dir_all(staging_dir)
+}
+
+fn swap_file(
+    file: &Path,
+    staging_dir: &Path,
+    target_dir: &Path,
+    backup_dir: &Path,
+) -> Result<(), std::io::Error> {
+    let target_file = target_dir.join(file);
+    if target_file.exists() {
+        let backup_file = backup_dir.join(BACKUP_FILES_DIR).join(file);
+        if let Some(parent) = backup_file.parent() {
+            std::fs::create_dir_all(parent)?
+        }
+        move_file(&target_file, &backup_file)?
+    } else if let Some(parent) = target_file.parent() {
+        std::fs::create_dir_all(parent)?
+    }
+    move_file(&staging_dir.join(file), &target_file)
+}
+
+/// Copied then removed when the file is on another filesystem
+fn move_file(source_file: &Path, target_file: &Path) -> Result<(), std::io::Error> {
+    match std::fs::rename(source_file, target_file) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            std::fs::copy(source_file, target_file)?;
+            std::fs::remove_file(source_file)
+        }
+        result => result,
+    }
+}
+
+/// Puts back the files saved by `swap_dir`, and removes the files it added
+pub fn restore_backup(target_dir: &Path, backup_dir: &Path) -> Result<(), std::io::Error> {
+    info!(
+        "Restore backup \"{}\" into \"{}\"",
+        backup_dir.to_string_lossy(),
         target_dir.to_string_lossy()
     );
-    Ok(zip_extract(archive_path, target_dir)?)
+    let added_files = std::fs::read_to_string(backup_dir.join(BACKUP_ADDED_FILES))?;
+    for file in added_files.lines().filter(|line| !line.is_empty()) {
+        match std::fs::remove_file(target_dir.join(file)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => (),
+        }
+    }
+    let files_dir = backup_dir.join(BACKUP_FILES_DIR);
+    for file in list_files(&files_dir)? {
+        let target_file = target_dir.join(&file);
+        if let Some(parent) = target_file.parent() {
+            std::fs::create_dir_all(parent)?
+        }
+        move_file(&files_dir.join(&file), &target_file)?
+    }
+    std::fs::remove_dir_all(backup_dir)
+}
+
+#[cfg(feature = "network")]
+fn download_file<R: Read + ?Sized>(reader: &mut R, path: &Path) -> Result<u64, Error> {
+    let mut file = File::create(path)?;
+    let size = std::io::copy(reader, &mut file)?;
+    if size > MAX_DOWNLOAD_SIZE {
+        return Err(Error::TooLarge(MAX_DOWNLOAD_SIZE));
+    }
+    Ok(size)
 }
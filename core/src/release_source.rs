@@ -0,0 +1,187 @@
+use crate::game::ffnx_installation::{FfnxInstallation, FFNX_REPO_NAME};
+use crate::game::installation::Edition;
+use crate::github::{self, GitHubRelease, GitHubReleaseAsset, GitHubReleases, LatestRelease};
+use crate::provision::{self, RemoteFile};
+use jiff::Timestamp;
+use regex_lite::Regex;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Where FFNx releases are looked up, releases use the GitHub format whatever the source
+pub trait ReleaseSource: fmt::Display {
+    fn list_releases(&self) -> Result<Vec<GitHubRelease>, provision::Error>;
+
+    fn latest_releases(&self) -> Result<LatestRelease, provision::Error> {
+        Ok(LatestRelease::from_releases(self.list_releases()?)?)
+    }
+
+    fn find_release_by_tag(&self, tag: &str) -> Result<Option<GitHubRelease>, provision::Error> {
+        Ok(self
+            .list_releases()?
+            .into_iter()
+            .find(|release| release.tag_name == tag))
+    }
+
+    /// The file to install when no release can be found
+    fn fallback_file(&self, _edition: &Edition) -> Option<RemoteFile> {
+        None
+    }
+}
+
+/// The releases of a GitHub repository
+pub struct GitHubSource {
+    pub repo_name: String,
+}
+
+impl GitHubSource {
+    pub fn new<S: Into<String>>(repo_name: S) -> Self {
+        Self {
+            repo_name: repo_name.into(),
+        }
+    }
+}
+
+impl Default for GitHubSource {
+    fn default() -> Self {
+        Self::new(FFNX_REPO_NAME)
+    }
+}
+
+impl fmt::Display for GitHubSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GitHub ({})", self.repo_name)
+    }
+}
+
+impl ReleaseSource for GitHubSource {
+    fn list_releases(&self) -> Result<Vec<GitHubRelease>, provision::Error> {
+        github::list_releases(&self.repo_name)
+    }
+
+    fn latest_releases(&self) -> Result<LatestRelease, provision::Error> {
+        github::find_last_release(&self.repo_name)
+    }
+
+    fn find_release_by_tag(&self, tag: &str) -> Result<Option<GitHubRelease>, provision::Error> {
+        match github::find_release_by_tag(&self.repo_name, tag) {
+            Ok(release) => Ok(Some(release)),
+            Err(provision::Error::HttpStatus(404)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn fallback_file(&self, edition: &Edition) -> Option<RemoteFile> {
+        if self.repo_name != FFNX_REPO_NAME {
+            return None;
+        }
+        FfnxInstallation::canary_file(edition)
+    }
+}
+
+/// A JSON file listing releases in the format of the GitHub API, over HTTP or `file://`
+pub struct ManifestSource {
+    pub url: String,
+}
+
+impl ManifestSource {
+    pub fn new<S: Into<String>>(url: S) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+impl fmt::Display for ManifestSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "manifest {}", self.url)
+    }
+}
+
+impl ReleaseSource for ManifestSource {
+    fn list_releases(&self) -> Result<Vec<GitHubRelease>, provision::Error> {
+        provision::get_json::<Vec<GitHubRelease>>(&self.url)
+    }
+
+    fn latest_releases(&self) -> Result<LatestRelease, provision::Error> {
+        Ok(provision::get_json::<GitHubReleases>(&self.url)?.latest_release)
+    }
+}
+
+/// A directory of FFNx archives, one release per version found in the file names
+/// (e.g. "FFNx-Steam-v1.21.0.zip"), archives named "canary" are prereleases
+pub struct LocalSource {
+    pub dir: PathBuf,
+}
+
+impl LocalSource {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl fmt::Display for LocalSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "directory \"{}\"", self.dir.to_string_lossy())
+    }
+}
+
+impl ReleaseSource for LocalSource {
+    fn list_releases(&self) -> Result<Vec<GitHubRelease>, provision::Error> {
+        let version_regex = Regex::new(r"v?(\d+(?:\.\d+)+)").unwrap();
+        let mut checksum_assets = Vec::new();
+        let mut releases: BTreeMap<String, (GitHubRelease, Timestamp)> = BTreeMap::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let asset = GitHubReleaseAsset {
+                browser_download_url: provision::file_url(&entry.path()),
+                name: name.clone(),
+                size: Some(metadata.len()),
+                digest: None,
+            };
+            let lowercase_name = name.to_ascii_lowercase();
+            if !lowercase_name.ends_with(".zip") {
+                checksum_assets.push(asset);
+                continue;
+            }
+            let Some(version) = version_regex.captures(&name).map(|c| c[1].to_string()) else {
+                info!("Ignore \"{}\", there is no version in its name", name);
+                continue;
+            };
+            let modified =
+                Timestamp::from_second(Timestamp::try_from(metadata.modified()?)?.as_second())?;
+            let (release, published_at) = releases.entry(version.clone()).or_insert_with(|| {
+                let release = GitHubRelease {
+                    tag_name: version,
+                    id: 0,
+                    assets: Vec::new(),
+                    prerelease: false,
+                    draft: false,
+                    published_at: None,
+                    body: None,
+                    html_url: String::new(),
+                };
+                (release, modified)
+            });
+            release.prerelease |= lowercase_name.contains("canary");
+            *published_at = modified.max(*published_at);
+            release.assets.push(asset)
+        }
+        let mut releases: Vec<(GitHubRelease, Timestamp)> = releases.into_values().collect();
+        releases.sort_by_key(|(_, published_at)| std::cmp::Reverse(*published_at));
+        Ok(releases
+            .into_iter()
+            .enumerate()
+            .map(|(id, (mut release, published_at))| {
+                release.id = id as i64;
+                release.published_at = Some(published_at.to_string());
+                // Checksum files are shared by every archive of the directory
+                release.assets.extend(checksum_assets.iter().cloned());
+                release
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,238 @@
+use crate::config::UpdateChannel;
+#[cfg(feature = "zip")]
+use crate::game::env::Env;
+use crate::github::{self, GitHubRelease, LatestRelease};
+#[cfg(windows)]
+use crate::os::regedit;
+#[cfg(feature = "zip")]
+use crate::provision::Progress;
+use crate::provision::{self, RemoteFile};
+use crate::version::Version;
+use regex_lite::Regex;
+use std::path::{Path, PathBuf};
+
+pub const MUMBA_REPO_NAME: &str = "myst6re/mumba";
+/// Where a portable package is extracted, next to the executable
+#[cfg(feature = "zip")]
+const UPDATE_DIR: &str = ".mumba-update";
+/// Where the files replaced by a portable package are kept until the next start
+const UPDATE_BACKUP_DIR: &str = ".mumba-update-backup";
+/// The files installed by the Debian package
+const DPKG_FILE_LIST: &str = "/var/lib/dpkg/info/mumba.list";
+/// The platform in the names of the published packages
+const PLATFORM: &str = if cfg!(windows) {
+    "windows64"
+} else {
+    "linux64"
+};
+
+/// The version of this build, CI sets it to the version in the name of the published packages
+pub const MUMBA_VERSION: &str = match option_env!("MOOMBA_VERSION") {
+    Some(version) => version,
+    None => env!("CARGO_PKG_VERSION"),
+};
+
+/// How Mumba was installed, to download the same kind of package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Package {
+    Msi,
+    Deb,
+    PortableZip,
+}
+
+impl Package {
+    pub fn current() -> Option<Self> {
+        if cfg!(windows) {
+            // Set by the MSI installer, see gui/wix/main.wxs
+            #[cfg(windows)]
+            if regedit::reg_key_exists(regedit::RegLocation::User, r"Software\Mumba\Mumba") {
+                return Some(Package::Msi);
+            }
+            Some(Package::PortableZip)
+        } else if cfg!(target_os = "linux") {
+            if is_dpkg_installed() {
+                Some(Package::Deb)
+            } else {
+                Some(Package::PortableZip)
+            }
+        } else {
+            None
+        }
+    }
+
+    /// The asset names are built in .github/workflows/build.yml
+    fn matches(&self, asset_name: &str, language: &str) -> bool {
+        match self {
+            Package::Msi => {
+                let culture = if language.starts_with("fr") {
+                    "-francais.msi"
+                } else {
+                    "-english.msi"
+                };
+                asset_name.contains("-setup-windows64") && asset_name.ends_with(culture)
+            }
+            Package::Deb => asset_name.ends_with("-linux64.deb"),
+            Package::PortableZip => asset_name.ends_with(&format!("-{}.zip", PLATFORM)),
+        }
+    }
+}
+
+/// Whether the running executable is one of the files of the Debian package
+fn is_dpkg_installed() -> bool {
+    let Ok(exe_path) = std::env::current_exe() else {
+        return false;
+    };
+    std::fs::read_to_string(DPKG_FILE_LIST)
+        .is_ok_and(|files| files.lines().any(|file| Path::new(file) == exe_path))
+}
+
+pub struct MumbaUpdate {
+    pub version: Version,
+    pub release: GitHubRelease,
+    pub remote: RemoteFile,
+    pub package: Package,
+}
+
+impl MumbaUpdate {
+    /// The GitHub page of the release, to read its notes
+    pub fn release_url(&self) -> String {
+        if !self.release.html_url.is_empty() {
+            return self.release.html_url.clone();
+        }
+        format!(
+            "https://github.com/{}/releases/tag/{}",
+            MUMBA_REPO_NAME, self.release.tag_name
+        )
+    }
+}
+
+pub enum SelfUpdateResult {
+    /// The installer is running, Mumba should exit
+    InstallerStarted,
+    /// The files are replaced, the new version is used on the next start
+    Replaced,
+    /// The package has to be installed by the user
+    Downloaded(PathBuf),
+}
+
+pub fn current_version() -> Version {
+    MUMBA_VERSION.parse().unwrap_or(Version {
+        numbers: vec![0],
+        prerelease: None,
+    })
+}
+
+/// Looks for a newer Mumba package on GitHub, `language` selects the MSI culture
+pub fn find_update(
+    update_channel: UpdateChannel,
+    language: &str,
+) -> Result<Option<MumbaUpdate>, provision::Error> {
+    let Some(package) = Package::current() else {
+        return Ok(None);
+    };
+    let releases = github::find_last_release(MUMBA_REPO_NAME)?;
+    Ok(find_update_in(
+        releases,
+        &current_version(),
+        package,
+        update_channel,
+        language,
+    ))
+}
+
+pub fn find_update_in(
+    releases: LatestRelease,
+    current_version: &Version,
+    package: Package,
+    update_channel: UpdateChannel,
+    language: &str,
+) -> Option<MumbaUpdate> {
+    let version_regex = Regex::new(r"^mumba-(.+?)-(?:setup-)?(?:windows64|linux64)").unwrap();
+    let candidates = match update_channel {
+        UpdateChannel::Stable => vec![releases.latest],
+        // Continuous builds are published as a prerelease
+        UpdateChannel::Beta | UpdateChannel::Alpha => vec![releases.latest, releases.prerelease],
+    };
+    candidates
+        .into_iter()
+        .flatten()
+        .filter_map(|release| {
+            let asset = release
+                .assets
+                .iter()
+                .find(|asset| package.matches(&asset.name, language))?;
+            let version: Version = version_regex.captures(&asset.name)?[1].parse().ok()?;
+            let remote = github::remote_file_from_asset(&release, asset);
+            Some(MumbaUpdate {
+                version,
+                release,
+                remote,
+                package,
+            })
+        })
+        .filter(|update| update.version > *current_version)
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// Removes the files replaced by the last update of a portable installation,
+/// the previous executable is not running anymore when the new one starts
+pub fn remove_backup() {
+    let Ok(mut mumba_dir) = std::env::current_exe() else {
+        return;
+    };
+    mumba_dir.pop();
+    let backup_dir = mumba_dir.join(UPDATE_BACKUP_DIR);
+    if backup_dir.exists() {
+        info!(
+            "Remove the previous version in \"{}\"",
+            backup_dir.to_string_lossy()
+        );
+        if let Err(e) = std::fs::remove_dir_all(&backup_dir) {
+            warn!("Cannot remove \"{}\": {}", backup_dir.to_string_lossy(), e)
+        }
+    }
+}
+
+/// Downloads the package, then runs the installer or replaces the files of a portable installation
+#[cfg(feature = "zip")]
+pub fn install(
+    update: &MumbaUpdate,
+    env: &Env,
+    progress: &dyn Fn(Progress),
+) -> Result<SelfUpdateResult, provision::Error> {
+    let package_path = provision::download(&update.remote, env, progress)?;
+    match update.package {
+        Package::Msi => {
+            crate::os::run_helper(
+                std::process::Command::new("msiexec")
+                    .arg("/i")
+                    .arg(&package_path),
+            )
+            .spawn()?;
+            Ok(SelfUpdateResult::InstallerStarted)
+        }
+        Package::Deb => Ok(SelfUpdateResult::Downloaded(package_path)),
+        Package::PortableZip => {
+            let mut mumba_dir = std::env::current_exe()?;
+            mumba_dir.pop();
+            let staging_dir = mumba_dir.join(UPDATE_DIR);
+            if staging_dir.exists() {
+                std::fs::remove_dir_all(&staging_dir)?
+            }
+            provision::extract_zip(&package_path, &staging_dir, progress)?;
+            // The archive contains a single "mumba-<version>-<platform>" directory
+            let mut entries = std::fs::read_dir(&staging_dir)?.collect::<Result<Vec<_>, _>>()?;
+            let root_dir = match entries.pop() {
+                Some(entry) if entries.is_empty() && entry.file_type()?.is_dir() => entry.path(),
+                _ => staging_dir.clone(),
+            };
+            // Running executables can be renamed but not their directory,
+            // so they are moved one by one to the backup
+            provision::swap_files(&root_dir, &mumba_dir, &mumba_dir.join(UPDATE_BACKUP_DIR))?;
+            if staging_dir.exists() {
+                std::fs::remove_dir_all(&staging_dir)?
+            }
+            Ok(SelfUpdateResult::Replaced)
+        }
+    }
+}
@@ -6,9 +6,7 @@ use keyvalues_parser::Vdf;
 use keyvalues_serde::from_vdf;
 #[cfg(feature = "steam")]
 use serde::Deserialize;
-#[cfg(feature = "steam")]
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(feature = "steam")]
 use std::{borrow::Cow, collections::HashMap, fs};
 
@@ -25,23 +23,169 @@ struct Library {
     apps: HashMap<u64, u64>,
 }
 
+/// An app of a Steam library, read from its `appmanifest_<appid>.acf` file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppManifest {
+    pub app_id: u64,
+    pub name: String,
+    /// The directory of the app in `steamapps/common`
+    pub install_dir: String,
+    pub build_id: u64,
+    /// The language selected in Steam, like "english"
+    pub language: Option<String>,
+    pub state_flags: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppState {
+    Installed,
+    /// Downloading or waiting for an update, the files may be incomplete
+    Updating,
+    NotInstalled,
+}
+
+impl AppManifest {
+    // EAppState flags of the Steam client
+    const STATE_UPDATE_REQUIRED: u64 = 2;
+    const STATE_FULLY_INSTALLED: u64 = 4;
+    const STATE_UPDATE_RUNNING: u64 = 256;
+    const STATE_UPDATE_PAUSED: u64 = 512;
+    const STATE_UPDATE_STARTED: u64 = 1024;
+
+    pub fn path(steamapps_dir: &Path, app_id: u64) -> PathBuf {
+        steamapps_dir.join(format!("appmanifest_{}.acf", app_id))
+    }
+
+    #[cfg(feature = "steam")]
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    #[cfg(feature = "steam")]
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let vdf = keyvalues_parser::parse(text).map(Vdf::from)?;
+        let app_state = vdf.value.get_obj().ok_or("AppState is not an object")?;
+        let get_str = |obj: &keyvalues_parser::Obj, key: &str| -> Option<String> {
+            obj.get(key)?.first()?.get_str().map(String::from)
+        };
+        let get_u64 = |key: &str| -> u64 {
+            get_str(app_state, key)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default()
+        };
+        Ok(AppManifest {
+            app_id: get_str(app_state, "appid")
+                .ok_or("Missing appid")?
+                .parse()?,
+            name: get_str(app_state, "name").unwrap_or_default(),
+            install_dir: get_str(app_state, "installdir").ok_or("Missing installdir")?,
+            build_id: get_u64("buildid"),
+            language: app_state
+                .get("UserConfig")
+                .and_then(|values| values.first()?.get_obj())
+                .and_then(|user_config| get_str(user_config, "language"))
+                .filter(|language| !language.is_empty()),
+            state_flags: get_u64("StateFlags"),
+        })
+    }
+
+    pub fn state(&self) -> AppState {
+        if self.state_flags
+            & (Self::STATE_UPDATE_REQUIRED
+                | Self::STATE_UPDATE_RUNNING
+                | Self::STATE_UPDATE_PAUSED
+                | Self::STATE_UPDATE_STARTED)
+            != 0
+        {
+            AppState::Updating
+        } else if self.state_flags & Self::STATE_FULLY_INSTALLED != 0 {
+            AppState::Installed
+        } else {
+            AppState::NotInstalled
+        }
+    }
+}
+
+/// How the Steam client is installed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SteamClient {
+    Native,
+    Flatpak,
+    Snap,
+}
+
 pub struct Steam {
     #[cfg(feature = "steam")]
     library_folders: Option<SteamLibraryFolders>,
     pub path: PathBuf,
+    pub client: SteamClient,
 }
 
 impl Steam {
+    /// The first Steam client found, see `steam_roots`
     pub fn from_config() -> Result<Self, Box<dyn std::error::Error>> {
         let path = get_steam_path()?;
+        let client = steam_client(&path);
+
+        Ok(Self::from_path(path, client))
+    }
 
-        Ok(Steam {
+    pub fn from_path(path: PathBuf, client: SteamClient) -> Self {
+        Steam {
             #[cfg(feature = "steam")]
             library_folders: Self::list_library_folders(&path).ok(),
             path,
+            client,
+        }
+    }
+
+    /// Every Steam client installed, several can coexist on Linux
+    #[cfg(windows)]
+    pub fn all() -> Vec<Self> {
+        Self::from_config().into_iter().collect()
+    }
+
+    /// Every Steam client installed, several can coexist on Linux
+    #[cfg(unix)]
+    pub fn all() -> Vec<Self> {
+        match std::env::var_os("HOME") {
+            Some(home) => steam_roots(Path::new(&home))
+                .into_iter()
+                .map(|(path, client)| Self::from_path(path, client))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The client whose root or libraries contain the app directory
+    pub fn find_owner(app_path: &Path) -> Option<Self> {
+        let canonical_path = std::fs::canonicalize(app_path).ok();
+        Self::all().into_iter().find(|steam| {
+            steam.library_paths().iter().any(|library_path| {
+                app_path.starts_with(library_path)
+                    || canonical_path
+                        .as_ref()
+                        .is_some_and(|path| path.starts_with(library_path))
+            })
         })
     }
 
+    /// The root of the client and the libraries added in it
+    pub fn library_paths(&self) -> Vec<&Path> {
+        #[allow(unused_mut)]
+        let mut paths = vec![self.path.as_path()];
+        #[cfg(feature = "steam")]
+        if let Some(library_folders) = &self.library_folders {
+            paths.extend(
+                library_folders
+                    .libraries
+                    .iter()
+                    .map(|lib| lib.path.as_path()),
+            )
+        }
+        paths
+    }
+
     #[allow(unused_variables)]
     pub fn find_app(&self, app_id: u64, app_name: &'static str) -> Option<PathBuf> {
         let steam_path = if cfg!(feature = "steam") {
@@ -57,7 +201,19 @@ impl Steam {
         } else {
             &self.path
         };
-        let app_path = steam_path.join("steamapps").join("common").join(app_name);
+        let steamapps_dir = steam_path.join("steamapps");
+        // The install directory can differ from the name of the app
+        #[cfg(feature = "steam")]
+        let install_dir = match AppManifest::from_file(&AppManifest::path(&steamapps_dir, app_id)) {
+            Ok(manifest) => manifest.install_dir,
+            Err(e) => {
+                warn!("Cannot read the manifest of the app {}: {}", app_id, e);
+                String::from(app_name)
+            }
+        };
+        #[cfg(not(feature = "steam"))]
+        let install_dir = String::from(app_name);
+        let app_path = steamapps_dir.join("common").join(install_dir);
         if app_path.exists() {
             Some(app_path)
         } else {
@@ -89,7 +245,7 @@ impl Steam {
             .join("libraryfolders.vdf");
         info!("list_library_folders \"{}\"", asset_path.to_string_lossy());
         let vdf_text = fs::read_to_string(asset_path)?;
-        let mut vdf = Vdf::parse(&vdf_text)?;
+        let mut vdf = keyvalues_parser::parse(&vdf_text).map(Vdf::from)?;
         let obj = vdf.value.get_mut_obj().unwrap();
 
         // Switch all the entries with keys that are an index (0, 1, ...) to `"libraries"`
@@ -122,18 +278,94 @@ pub fn get_steam_path() -> Result<PathBuf, crate::os::regedit::Error> {
     Ok(PathBuf::from(value))
 }
 
+/// Steam roots of the native, Flatpak and Snap clients, in the order of preference
+#[cfg(unix)]
+const STEAM_ROOTS: [(&str, SteamClient); 7] = [
+    (".steam/steam", SteamClient::Native),
+    (".steam/root", SteamClient::Native),
+    (".local/share/Steam", SteamClient::Native),
+    (
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+        SteamClient::Flatpak,
+    ),
+    (
+        ".var/app/com.valvesoftware.Steam/.steam/steam",
+        SteamClient::Flatpak,
+    ),
+    ("snap/steam/common/.local/share/Steam", SteamClient::Snap),
+    ("snap/steam/common/.steam/steam", SteamClient::Snap),
+];
+
+/// The Steam roots found in the home directory, symbolic links to the same root are listed once
+#[cfg(unix)]
+pub fn steam_roots(home: &Path) -> Vec<(PathBuf, SteamClient)> {
+    let mut roots: Vec<(PathBuf, SteamClient)> = Vec::new();
+    for (relative_path, client) in STEAM_ROOTS {
+        let Ok(path) = std::fs::canonicalize(home.join(relative_path)) else {
+            continue;
+        };
+        if path.join("steamapps").is_dir() && !roots.iter().any(|(root, _)| *root == path) {
+            roots.push((path, client))
+        }
+    }
+    roots
+}
+
+#[cfg(windows)]
+fn steam_client(_path: &Path) -> SteamClient {
+    SteamClient::Native
+}
+
+#[cfg(unix)]
+fn steam_client(path: &Path) -> SteamClient {
+    let path = path.to_string_lossy();
+    if path.contains("/.var/app/com.valvesoftware.Steam/") {
+        SteamClient::Flatpak
+    } else if path.contains("/snap/steam/") {
+        SteamClient::Snap
+    } else {
+        SteamClient::Native
+    }
+}
+
 #[cfg(unix)]
 pub fn get_steam_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let home = std::env::var("HOME")?;
-    Ok(std::fs::canonicalize(format!("{}/.steam/steam", home))?)
+    match steam_roots(Path::new(&home)).into_iter().next() {
+        Some((path, client)) => {
+            info!(
+                "Use {:?} Steam client at \"{}\"",
+                client,
+                path.to_string_lossy()
+            );
+            Ok(path)
+        }
+        None => Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "No Steam client found",
+        ))),
+    }
 }
 
+/// steam.exe in the Steam root
 #[cfg(windows)]
-pub fn get_steam_exe() -> Result<PathBuf, crate::os::regedit::Error> {
-    Ok(get_steam_path()?.join("steam.exe"))
+pub fn steam_exe(steam_path: &Path, _client: SteamClient) -> PathBuf {
+    steam_path.join("steam.exe")
 }
 
+/// The command starting the Steam client, Flatpak and Snap export one in their bin directory
 #[cfg(unix)]
-pub fn get_steam_exe() -> Result<PathBuf, Box<dyn std::error::Error>> {
-    Ok(PathBuf::from("steam"))
+pub fn steam_exe(_steam_path: &Path, client: SteamClient) -> PathBuf {
+    match client {
+        SteamClient::Native => PathBuf::from("steam"),
+        SteamClient::Flatpak => {
+            let user_export = std::env::var_os("HOME").map(|home| {
+                Path::new(&home).join(".local/share/flatpak/exports/bin/com.valvesoftware.Steam")
+            });
+            user_export.filter(|path| path.exists()).unwrap_or_else(|| {
+                PathBuf::from("/var/lib/flatpak/exports/bin/com.valvesoftware.Steam")
+            })
+        }
+        SteamClient::Snap => PathBuf::from("/snap/bin/steam"),
+    }
 }
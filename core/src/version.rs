@@ -0,0 +1,105 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("Invalid version: {0}")]
+pub struct ParseError(String);
+
+/// A version like "1.21.0" or "0.2.0-continuous.42", ordered like semver:
+/// missing numbers count as 0 and prereleases come before the release
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub numbers: Vec<u64>,
+    pub prerelease: Option<String>,
+}
+
+impl FromStr for Version {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let version = s.trim();
+        let version = version
+            .strip_prefix(['v', 'V'])
+            .unwrap_or(version)
+            .split('+')
+            .next()
+            .unwrap_or_default();
+        let (numbers, prerelease) = match version.split_once('-') {
+            Some((numbers, prerelease)) => (numbers, Some(String::from(prerelease))),
+            None => (version, None),
+        };
+        let numbers = numbers
+            .split('.')
+            .map(|n| n.parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| ParseError(String::from(s)))?;
+        Ok(Self {
+            numbers,
+            prerelease: prerelease.filter(|p| !p.is_empty()),
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numbers: Vec<String> = self.numbers.iter().map(u64::to_string).collect();
+        write!(f, "{}", numbers.join("."))?;
+        match &self.prerelease {
+            Some(prerelease) => write!(f, "-{}", prerelease),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.numbers.len().max(other.numbers.len());
+        let number = |v: &Self, i: usize| v.numbers.get(i).copied().unwrap_or(0);
+        (0..len)
+            .map(|i| number(self, i).cmp(&number(other, i)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| match (&self.prerelease, &other.prerelease) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => cmp_prerelease(a, b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Version {}
+
+fn cmp_prerelease(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use mumba_core::config::Config;
+
+#[test]
+fn it_pins_ffnx_version() {
+    let mut config = Config::new();
+    assert_eq!(config.ffnx_version().unwrap(), None);
+    config.set_ffnx_version(Some("1.19.1"));
+    assert_eq!(config.ffnx_version().unwrap(), Some(String::from("1.19.1")));
+    config.set_ffnx_version(None);
+    assert_eq!(config.ffnx_version().unwrap(), None);
+}
+
+#[cfg(feature = "network")]
+#[test]
+fn it_reads_http_config() {
+    let config_path = std::env::temp_dir().join("mumba_test_http_config.toml");
+    std::fs::write(
+        &config_path,
+        "[http]\nproxy = \"http://proxy.example.com:3128\"\ntimeout = 600\ngithub_token = \"\"\n",
+    )
+    .unwrap();
+    let http_config = Config::from_file(&config_path)
+        .unwrap()
+        .http_config()
+        .unwrap();
+    assert_eq!(
+        http_config.proxy,
+        Some(String::from("http://proxy.example.com:3128"))
+    );
+    assert_eq!(
+        http_config.timeout,
+        Some(std::time::Duration::from_secs(600))
+    );
+    assert_eq!(http_config.connect_timeout, None);
+    assert_eq!(http_config.github_token, None);
+    assert!(Config::new().http_config().is_ok());
+    std::fs::remove_file(&config_path).unwrap();
+}
+
+#[cfg(feature = "network")]
+#[test]
+fn it_reads_release_source() {
+    let mut config = Config::new();
+    assert_eq!(
+        config.release_source().unwrap().to_string(),
+        "GitHub (julianxhokaxhiu/FFNx)"
+    );
+    let config_path = std::env::temp_dir().join("mumba_test_release_source.toml");
+    std::fs::write(
+        &config_path,
+        "[release_source]\ntype = \"manifest\"\nurl = \"https://ffnx.example.com/releases.json\"\n",
+    )
+    .unwrap();
+    config = Config::from_file(&config_path).unwrap();
+    assert_eq!(
+        config.release_source().unwrap().to_string(),
+        "manifest https://ffnx.example.com/releases.json"
+    );
+    std::fs::write(&config_path, "[release_source]\ntype = \"local\"\n").unwrap();
+    config = Config::from_file(&config_path).unwrap();
+    assert!(config.release_source().is_err());
+    std::fs::remove_file(&config_path).unwrap();
+}
+
+#[cfg(feature = "network")]
+#[test]
+fn it_throttles_update_checks() {
+    let mut config = Config::new();
+    assert!(config.is_update_check_due());
+    config.set_update_checked();
+    assert!(config.last_update_check().unwrap().is_some());
+    assert!(!config.is_update_check_due());
+    config.use_profile("remastered");
+    assert!(config.is_update_check_due());
+    config.use_profile("default");
+    config.set_ffnx_update(Some("1.20.0"));
+    assert_eq!(config.ffnx_update().unwrap().as_deref(), Some("1.20.0"));
+    config.set_ffnx_update(None);
+    assert_eq!(config.ffnx_update().unwrap(), None);
+
+    let config_path = std::env::temp_dir().join("mumba_test_update_check.toml");
+    std::fs::write(
+        &config_path,
+        "last_update_check = \"2025-01-01T00:00:00Z\"\n",
+    )
+    .unwrap();
+    config = Config::from_file(&config_path).unwrap();
+    assert!(config.is_update_check_due());
+    config.set_check_update_on_startup(false);
+    assert!(!config.is_update_check_due());
+    std::fs::remove_file(&config_path).unwrap();
+}
+
+#[test]
+fn it_keeps_settings_per_profile() {
+    let config_path = std::env::temp_dir().join("mumba_test_profiles.toml");
+    let mut config = Config::new();
+    config.set_ffnx_version(Some("1.19.1"));
+    config.set_current_profile("remastered");
+    assert_eq!(config.ffnx_version().unwrap(), None);
+    config.set_ffnx_version(Some("1.20.0"));
+    config.save(&config_path).unwrap();
+
+    let mut config = Config::from_file(&config_path).unwrap();
+    assert_eq!(config.profile(), "remastered");
+    assert_eq!(config.profiles(), ["default", "remastered"]);
+    assert_eq!(config.ffnx_version().unwrap(), Some(String::from("1.20.0")));
+    config.use_profile("default");
+    assert_eq!(config.ffnx_version().unwrap(), Some(String::from("1.19.1")));
+    config.set_current_profile("remastered");
+    assert!(!config.remove_profile("modded"));
+    assert_eq!(config.profile(), "remastered");
+    assert!(config.remove_profile("remastered"));
+    assert_eq!(config.profile(), "default");
+    assert!(!config.remove_profile("default"));
+    assert_eq!(config.profiles(), ["default"]);
+    std::fs::remove_file(&config_path).unwrap();
+}
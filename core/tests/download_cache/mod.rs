@@ -0,0 +1,69 @@
+use mumba_core::download_cache::DownloadCache;
+use mumba_core::provision::RemoteFile;
+use std::time::{Duration, SystemTime};
+
+fn set_last_used(path: &std::path::Path, seconds_ago: u64) {
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(seconds_ago))
+        .unwrap()
+}
+
+#[test]
+fn it_keys_archives_by_url() {
+    let cache = DownloadCache::new(std::env::temp_dir().join("mumba_test_cache_keys"), 10);
+    let stable = cache.path_for(
+        "https://github.com/julianxhokaxhiu/FFNx/releases/download/1.20.1/FFNx-Steam.zip",
+    );
+    let beta = cache.path_for(
+        "https://github.com/julianxhokaxhiu/FFNx/releases/download/1.21.0/FFNx-Steam.zip",
+    );
+    assert_ne!(stable, beta);
+    assert!(stable.to_string_lossy().ends_with("-FFNx-Steam.zip"));
+}
+
+#[test]
+fn it_evicts_least_recently_used_archives() {
+    let dir = std::env::temp_dir().join("mumba_test_cache_evict");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let cache = DownloadCache::new(dir.clone(), 10);
+    let old = RemoteFile::new("https://example.com/old.zip");
+    let recent = RemoteFile::new("https://example.com/recent.zip");
+    let used = RemoteFile::new("https://example.com/used.zip");
+    for (remote, seconds_ago) in [(&old, 300), (&recent, 100), (&used, 200)] {
+        let path = cache.path_for(&remote.url);
+        std::fs::write(&path, "1234").unwrap();
+        set_last_used(&path, seconds_ago)
+    }
+
+    assert!(cache.get(&used).is_some());
+    cache.evict().unwrap();
+    assert!(cache.get(&old).is_none());
+    assert!(cache.get(&recent).is_some());
+    assert!(cache.get(&used).is_some());
+
+    assert_eq!(cache.clear().unwrap(), 8);
+    assert!(cache.entries().unwrap().is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_drops_archives_with_wrong_checksum() {
+    let dir = std::env::temp_dir().join("mumba_test_cache_checksum");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let cache = DownloadCache::new(dir.clone(), 1000);
+    let mut remote = RemoteFile::new("https://example.com/FFNx.zip");
+    std::fs::write(cache.path_for(&remote.url), "abc").unwrap();
+    remote.sha256 = Some(String::from(
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    ));
+    assert!(cache.get(&remote).is_some());
+    remote.size = Some(4);
+    assert!(cache.get(&remote).is_none());
+    assert!(!cache.path_for(&remote.url).exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,41 @@
+use std::path::{Path, PathBuf};
+
+/// The smallest PE32 executable pelite accepts: headers and one section with a `ret`
+pub fn synthetic_pe32(dir: &Path, characteristics: u16) -> PathBuf {
+    let mut bytes = vec![0u8; 0x400];
+    let mut write =
+        |offset: usize, value: &[u8]| bytes[offset..offset + value.len()].copy_from_slice(value);
+    write(0x00, b"MZ");
+    write(0x3C, &0x40u32.to_le_bytes());
+    write(0x40, b"PE\0\0");
+    // File header
+    write(0x44, &0x014Cu16.to_le_bytes()); // i386
+    write(0x46, &1u16.to_le_bytes());
+    write(0x54, &0xE0u16.to_le_bytes());
+    write(0x56, &characteristics.to_le_bytes());
+    // Optional header
+    write(0x58, &0x010Bu16.to_le_bytes());
+    write(0x68, &0x1000u32.to_le_bytes()); // Entry point
+    write(0x74, &0x0040_0000u32.to_le_bytes()); // Image base
+    write(0x78, &0x1000u32.to_le_bytes()); // Section alignment
+    write(0x7C, &0x0200u32.to_le_bytes()); // File alignment
+    write(0x80, &4u16.to_le_bytes()); // OS version
+    write(0x88, &4u16.to_le_bytes()); // Subsystem version
+    write(0x90, &0x2000u32.to_le_bytes()); // Size of image
+    write(0x94, &0x0200u32.to_le_bytes()); // Size of headers
+    write(0x9C, &2u16.to_le_bytes()); // GUI
+    write(0xB4, &16u32.to_le_bytes()); // Number of data directories
+
+    // Section
+    write(0x138, b".text\0\0\0");
+    write(0x140, &0x10u32.to_le_bytes());
+    write(0x144, &0x1000u32.to_le_bytes());
+    write(0x148, &0x0200u32.to_le_bytes());
+    write(0x14C, &0x0200u32.to_le_bytes());
+    write(0x15C, &0x6000_0020u32.to_le_bytes());
+    write(0x200, &[0xC3]);
+    std::fs::create_dir_all(dir).unwrap();
+    let path = dir.join("FF8_EN.exe");
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
@@ -0,0 +1,130 @@
+use mumba_core::game::discovery;
+use mumba_core::game::installation::InstallationSource;
+#[cfg(windows)]
+use mumba_core::os::regedit;
+use std::path::Path;
+#[cfg(windows)]
+use std::process::Command;
+
+fn create_game(app_path: &Path, exe_name: &str) {
+    std::fs::create_dir_all(app_path).unwrap();
+    std::fs::write(app_path.join(exe_name), "").unwrap();
+}
+
+#[cfg(feature = "discovery")]
+#[test]
+fn it_finds_installations_of_other_launchers() {
+    let home = std::env::temp_dir().join("mumba_test_discovery");
+    let _ = std::fs::remove_dir_all(&home);
+
+    let heroic_game = home.join("Games").join("Heroic").join("FF8");
+    create_game(&heroic_game, "ff8.exe");
+    let heroic_dir = home.join(".config").join("heroic").join("gog_store");
+    std::fs::create_dir_all(&heroic_dir).unwrap();
+    std::fs::write(
+        heroic_dir.join("installed.json"),
+        format!(
+            r#"{{"installed": [{{"appName": "1", "install_path": "{}", "platform": "windows"}}]}}"#,
+            heroic_game.to_string_lossy()
+        ),
+    )
+    .unwrap();
+    let legendary_game = home.join("Games").join("Heroic").join("FF8 Epic");
+    create_game(&legendary_game, "FF8.exe");
+    let legendary_dir = home.join(".config/heroic/legendaryConfig/legendary");
+    std::fs::create_dir_all(&legendary_dir).unwrap();
+    std::fs::write(
+        legendary_dir.join("installed.json"),
+        serde_json::json!({"ff8": {"app_name": "ff8", "install_path": legendary_game}}).to_string(),
+    )
+    .unwrap();
+
+    let lutris_game = home.join("Games").join("Lutris");
+    create_game(&lutris_game, "FF8.exe");
+    let lutris_dir = home.join(".local/share/lutris/games");
+    std::fs::create_dir_all(&lutris_dir).unwrap();
+    std::fs::write(
+        lutris_dir.join("final-fantasy-viii-1.yml"),
+        format!(
+            "game:\n  exe: {}\n  prefix: /nonexistent\nsystem: {{}}\n",
+            lutris_game.join("FF8.exe").to_string_lossy()
+        ),
+    )
+    .unwrap();
+
+    let bottle = home.join(".local/share/bottles/bottles/FF8");
+    create_game(&bottle.join("drive_c/Games/Final Fantasy VIII"), "FF8.exe");
+    std::fs::write(
+        bottle.join("system.reg"),
+        "WINE REGISTRY Version 2\n\n\
+        [Software\\\\Wow6432Node\\\\Square Soft, Inc\\\\Final Fantasy VIII\\\\1.00] 1700000000\n\
+        #time=1da0000000000000\n\
+        \"AppPath\"=\"C:\\\\Games\\\\Final Fantasy VIII\\\\\"\n",
+    )
+    .unwrap();
+
+    let installations = discovery::search_in_home(&home);
+    let sources: Vec<InstallationSource> = installations.iter().map(|i| i.source).collect();
+    assert_eq!(
+        sources,
+        vec![
+            InstallationSource::Heroic,
+            InstallationSource::Heroic,
+            InstallationSource::Lutris,
+            InstallationSource::Bottles
+        ]
+    );
+    assert_eq!(installations[0].exe_name, "ff8.exe");
+    assert_eq!(installations[1].app_path, legendary_game);
+    assert_eq!(
+        installations[3].app_path,
+        bottle.join("drive_c/Games/Final Fantasy VIII")
+    );
+}
+
+#[cfg(windows)]
+#[test]
+fn it_finds_gog_galaxy_games_in_the_registry() {
+    let games_key = r"Software\mumba_test_discovery\Games";
+    let reg = |args: &[&str]| {
+        assert!(Command::new("reg").args(args).status().unwrap().success());
+    };
+    let game_path = std::env::temp_dir().join("mumba_test_gog_galaxy");
+    let _ = std::fs::remove_dir_all(&game_path);
+    create_game(&game_path, "FF8.exe");
+    let game_key = format!(r"HKCU\{}\1207658903", games_key);
+    reg(&[
+        "add",
+        &game_key,
+        "/v",
+        "path",
+        "/d",
+        &game_path.to_string_lossy(),
+        "/f",
+    ]);
+    reg(&["add", &format!(r"HKCU\{}\1207658904", games_key), "/f"]);
+
+    let mut subkeys = regedit::reg_subkeys(
+        regedit::RegTarget::None,
+        regedit::RegLocation::User,
+        games_key,
+    );
+    subkeys.sort();
+    let installations = discovery::search_gog_galaxy_key(
+        regedit::RegTarget::None,
+        regedit::RegLocation::User,
+        games_key,
+    );
+    reg(&["delete", r"HKCU\Software\mumba_test_discovery", "/f"]);
+
+    assert_eq!(subkeys, vec!["1207658903", "1207658904"]);
+    assert!(regedit::reg_subkeys(
+        regedit::RegTarget::None,
+        regedit::RegLocation::User,
+        r"Software\mumba_test_discovery\Missing"
+    )
+    .is_empty());
+    assert_eq!(installations.len(), 1);
+    assert_eq!(installations[0].source, InstallationSource::Gog);
+    assert_eq!(installations[0].app_path, game_path);
+}
@@ -0,0 +1,170 @@
+use mumba_core::game::fingerprint::{self, Criterion, Fingerprint};
+use mumba_core::game::installation::{Edition, Installation};
+use std::path::{Path, PathBuf};
+
+const I386: u16 = 0x14C;
+const AMD64: u16 = 0x8664;
+
+/// A PE header for the machine, followed by the values read by the probes
+fn fixture_exe(dir: &Path, name: &str, machine: u16, probes: &[(usize, u32)]) -> PathBuf {
+    let mut bytes = vec![0u8; 0x1600];
+    bytes[0..2].copy_from_slice(b"MZ");
+    bytes[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    bytes[0x80..0x84].copy_from_slice(b"PE\0\0");
+    bytes[0x84..0x86].copy_from_slice(&machine.to_le_bytes());
+    for (offset, value) in probes {
+        bytes[*offset..*offset + 4].copy_from_slice(&value.to_le_bytes())
+    }
+    std::fs::create_dir_all(dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn identify(exe_path: &Path) -> Option<&'static Fingerprint> {
+    fingerprint::identify(exe_path).unwrap()
+}
+
+fn describe(fingerprint: Option<&Fingerprint>) -> String {
+    match fingerprint {
+        Some(found) => format!(
+            "{:?} {:?} {:?}",
+            found.edition, found.version, found.publisher
+        ),
+        None => String::from("unknown"),
+    }
+}
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn it_identifies_standard_executables() {
+    let dir = test_dir("mumba_test_fingerprint_standard");
+    let cases: [(u32, u32, u32, &str); 14] = [
+        (0x3885048D, 0x00159618, 0, "Some(V120) Some(EaUs)"),
+        (0x3885048D, 0x001597C8, 0, "Some(V120NV) Some(EaUs)"),
+        (0x1085048D, 0x00159B48, 0, "Some(V120) Some(EidosFr)"),
+        (0x1085048D, 0x00159CF8, 0, "Some(V120NV) Some(EidosFr)"),
+        (0xA885048D, 0x00159C48, 0, "Some(V120) Some(EidosDe)"),
+        (0xA885048D, 0x00159DF8, 0, "Some(V120NV) Some(EidosDe)"),
+        (0x8085048D, 0x00159C38, 0, "Some(V120) Some(EidosSp)"),
+        (0x8085048D, 0x00159DE8, 0, "Some(V120NV) Some(EidosSp)"),
+        (0xB885048D, 0x00159BC8, 0, "Some(V120) Some(EidosIt)"),
+        (0xB885048D, 0x00159D78, 0, "Some(V120NV) Some(EidosIt)"),
+        (0x2885048D, 0x00159598, 0, "Some(V120) Some(EidosUk)"),
+        (0x2885048D, 0x00159748, 0, "Some(V120NV) Some(EidosUk)"),
+        (0x01B6E9CC, 0x7C8DFFC9, 0x24AC, "Some(V120NV) Some(EaJp)"),
+        (0x01B6E9CC, 0x7C8DFFC9, 0, "Some(V120) Some(EaJp)"),
+    ];
+    let mut found = Vec::new();
+    for (check1, check2, check3, expected) in cases {
+        let exe_path = fixture_exe(
+            &dir,
+            "FF8.exe",
+            I386,
+            &[(0x1004, check1), (0x1404, check2), (0x1010, check3)],
+        );
+        let fingerprint = identify(&exe_path);
+        assert_eq!(
+            describe(fingerprint),
+            format!("Standard {}", expected),
+            "{:#X} {:#X} {:#X}",
+            check1,
+            check2,
+            check3
+        );
+        found.push(fingerprint.unwrap() as *const Fingerprint)
+    }
+    assert_eq!(
+        format!(
+            "{:?}",
+            Installation::get_version_from_exe(&dir.join("FF8.exe")).unwrap()
+        ),
+        "Some((V120, EaJp))"
+    );
+    found.sort();
+    found.dedup();
+    let standard_count = fingerprint::database()
+        .iter()
+        .filter(|fingerprint| fingerprint.edition == Edition::Standard)
+        .count();
+    assert_eq!(found.len(), standard_count, "A fingerprint has no fixture");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_identifies_steam_and_remastered_executables() {
+    let dir = test_dir("mumba_test_fingerprint_steam");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("af3dn.p"), "").unwrap();
+    for language in ["EN", "FR", "DE", "IT", "ES", "JP"] {
+        let exe_path = fixture_exe(&dir, &format!("FF8_{}.exe", language), I386, &[]);
+        assert_eq!(
+            describe(identify(&exe_path)),
+            "Steam None None",
+            "{}",
+            language
+        );
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let dir = test_dir("mumba_test_fingerprint_remastered");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("FFVIII_LAUNCHER.exe"), "").unwrap();
+    let exe_path = fixture_exe(&dir, "FFVIII.exe", AMD64, &[]);
+    assert_eq!(describe(identify(&exe_path)), "Remastered None None");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_does_not_identify_renamed_executables() {
+    let dir = test_dir("mumba_test_fingerprint_renamed");
+    // An unrelated program renamed, alone in its directory
+    for name in ["FF8.exe", "FF8_EN.exe", "FFVIII.exe"] {
+        let exe_path = fixture_exe(&dir, name, I386, &[(0x1004, 0x12345678)]);
+        assert_eq!(describe(identify(&exe_path)), "unknown", "{}", name);
+    }
+    // Next to the Steam driver, but built for another CPU
+    std::fs::write(dir.join("AF3DN.P"), "").unwrap();
+    let exe_path = fixture_exe(&dir, "FF8_EN.exe", AMD64, &[]);
+    assert_eq!(describe(identify(&exe_path)), "unknown");
+    // Not even a PE executable
+    std::fs::write(&exe_path, "#!/bin/sh\n").unwrap();
+    assert_eq!(describe(identify(&exe_path)), "unknown");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_parses_fingerprints() {
+    let fingerprints = fingerprint::parse(
+        "# Comment\nstandard V100 EidosFr size=1234 timestamp=0x3A1B2C3D probe:16=0x24AC\n",
+    )
+    .unwrap();
+    assert_eq!(
+        fingerprints[0].criteria,
+        [
+            Criterion::Size(1234),
+            Criterion::Timestamp(0x3A1B2C3D),
+            Criterion::Probe(16, 0x24AC)
+        ]
+    );
+    assert_eq!(
+        fingerprint::parse("standard V100 EidosFr\n")
+            .unwrap_err()
+            .line,
+        1
+    );
+    assert!(fingerprint::parse("standard V130 EidosFr size=1\n").is_err());
+    assert!(fingerprint::parse("standard - - sha256=abc\n").is_err());
+    assert_eq!(
+        fingerprint::parse("steam - - machine=0x14C sibling=AF3DN.P\n").unwrap()[0].criteria,
+        [
+            Criterion::Machine(0x14C),
+            Criterion::Sibling(String::from("AF3DN.P"))
+        ]
+    );
+}
@@ -0,0 +1,101 @@
+use mumba_core::game::install_journal::{Action, InstallJournal, State};
+use mumba_core::game::install_manifest::{InstallManifest, ManifestEntry};
+use std::io::Write;
+
+#[test]
+fn it_resumes_then_undoes_an_interrupted_install() {
+    let dir = std::env::temp_dir().join("mumba_test_install_journal");
+    let _ = std::fs::remove_dir_all(&dir);
+    let (app_path, ffnx_dir, journal_dir) =
+        (dir.join("game"), dir.join("ffnx"), dir.join("journal"));
+    let (staging_dir, backup_dir) = (dir.join("staging"), dir.join("backup"));
+    std::fs::create_dir_all(&app_path).unwrap();
+    std::fs::create_dir_all(&ffnx_dir).unwrap();
+    std::fs::write(app_path.join("FF8.exe"), "exe").unwrap();
+    std::fs::write(ffnx_dir.join("FFNx.toml"), "user config").unwrap();
+    let zip_path = dir.join("FFNx.zip");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("FFNx.toml", options).unwrap();
+    zip.write_all(b"show_fps = false").unwrap();
+    zip.start_file("AF3DN.P", options).unwrap();
+    zip.write_all(b"ffnx").unwrap();
+    zip.finish().unwrap();
+
+    let patch = |path: &std::path::Path| std::fs::write(path, "patched").map(|()| true);
+
+    // Interrupted before the commit
+    let mut journal = InstallJournal::new(journal_dir.clone()).unwrap();
+    journal.begin(&ffnx_dir).unwrap();
+    assert!(!journal.is_resumed());
+    journal
+        .extract_zip(&zip_path, &staging_dir, &|_| ())
+        .unwrap();
+    journal
+        .swap_dir(&staging_dir, &ffnx_dir, &backup_dir)
+        .unwrap();
+    journal
+        .copy_file(&app_path.join("FF8.exe"), &ffnx_dir.join("FF8.exe"))
+        .unwrap();
+    journal
+        .patch_file(&ffnx_dir.join("FFNx.toml"), patch)
+        .unwrap();
+
+    let mut journal = InstallJournal::new(journal_dir.clone()).unwrap();
+    assert_eq!(*journal.state(), State::InProgress);
+    assert_eq!(journal.steps().len(), 5);
+    assert_eq!(journal.steps()[0].action, Action::Extract(zip_path.clone()));
+    let step = &journal.steps()[2];
+    assert_eq!(
+        step.action,
+        Action::Swap {
+            staging_dir: staging_dir.clone(),
+            backup_dir: backup_dir.clone()
+        }
+    );
+    assert_eq!(step.target, ffnx_dir);
+    assert!(step.is_done());
+    assert!(journal.steps()[3].before.is_none());
+    let step = &journal.steps()[4];
+    assert_eq!(step.action, Action::Patch);
+    assert!(step.before.is_some() && step.after.is_some() && step.before != step.after);
+    assert_eq!(
+        InstallManifest::open(&ffnx_dir).unwrap().entries(),
+        [
+            ManifestEntry::CreatedFile(ffnx_dir.join("AF3DN.P")),
+            ManifestEntry::CreatedFile(ffnx_dir.join("FF8.exe"))
+        ]
+    );
+
+    // Resumed: the steps already done are skipped
+    journal.begin(&ffnx_dir).unwrap();
+    assert!(journal.is_resumed());
+    assert!(journal.is_swapped(&staging_dir, &ffnx_dir));
+    journal
+        .swap_dir(&staging_dir, &ffnx_dir, &backup_dir)
+        .unwrap();
+    journal
+        .copy_file(&app_path.join("FF8.exe"), &ffnx_dir.join("FF8.exe"))
+        .unwrap();
+    journal
+        .patch_file(&ffnx_dir.join("FFNx.toml"), patch)
+        .unwrap();
+    assert_eq!(journal.steps().len(), 5);
+
+    journal.undo().unwrap();
+    assert_eq!(*journal.state(), State::Undone);
+    assert_eq!(
+        std::fs::read_to_string(ffnx_dir.join("FFNx.toml")).unwrap(),
+        "user config"
+    );
+    assert!(!ffnx_dir.join("AF3DN.P").exists());
+    assert!(!ffnx_dir.join("FF8.exe").exists());
+    assert!(!backup_dir.exists());
+    assert!(app_path.join("FF8.exe").exists());
+    assert!(InstallManifest::open(&ffnx_dir).unwrap().is_empty());
+    assert_eq!(
+        *InstallJournal::new(journal_dir).unwrap().state(),
+        State::Undone
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
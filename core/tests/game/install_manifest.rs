@@ -0,0 +1,108 @@
+use mumba_core::game::install_manifest::{InstallManifest, ManifestEntry};
+use mumba_core::provision;
+
+#[test]
+fn it_puts_back_the_game_files_on_undo() {
+    let app_path = std::env::temp_dir().join("mumba_test_install_manifest");
+    let _ = std::fs::remove_dir_all(&app_path);
+    std::fs::create_dir_all(&app_path).unwrap();
+    std::fs::write(app_path.join("FF8.exe"), "original exe").unwrap();
+    std::fs::write(app_path.join("eax.dll"), "original eax").unwrap();
+    std::fs::write(app_path.join("FF8_Launcher.exe"), "original launcher").unwrap();
+
+    let mut manifest = InstallManifest::create(&app_path).unwrap();
+    assert!(manifest.is_empty());
+    let staging_dir = provision::sibling_dir(&app_path, ".mumba-staging");
+    std::fs::create_dir_all(staging_dir.join("shaders")).unwrap();
+    std::fs::write(staging_dir.join("eax.dll"), "ffnx").unwrap();
+    std::fs::write(staging_dir.join("shaders").join("main.frag"), "").unwrap();
+    // Like `InstallJournal::swap_dir`, the files the swap adds are recorded first
+    manifest
+        .record(ManifestEntry::CreatedFile(
+            app_path.join("shaders").join("main.frag"),
+        ))
+        .unwrap();
+    let backup_dir = InstallManifest::original_files_path(&app_path);
+    provision::swap_dir(&staging_dir, &app_path, &backup_dir).unwrap();
+    manifest
+        .record(ManifestEntry::Swapped {
+            target_dir: app_path.clone(),
+            backup_dir,
+        })
+        .unwrap();
+    let launcher_backup = app_path.join("FF8_Launcher.exe.bak");
+    provision::rename_file(&app_path.join("FF8_Launcher.exe"), &launcher_backup).unwrap();
+    std::fs::write(app_path.join("FF8_Launcher.exe"), "mumba launcher").unwrap();
+    manifest
+        .record(ManifestEntry::Replaced {
+            path: app_path.join("FF8_Launcher.exe"),
+            backup: launcher_backup,
+        })
+        .unwrap();
+    std::fs::write(app_path.join("FFNx.toml"), "").unwrap();
+    manifest
+        .record(ManifestEntry::CreatedFile(app_path.join("FFNx.toml")))
+        .unwrap();
+
+    let manifest = InstallManifest::open(&app_path).unwrap();
+    assert_eq!(manifest.entries().len(), 4);
+    assert!(!manifest.created_dir(&app_path));
+    manifest.undo().unwrap();
+
+    assert_eq!(
+        provision::list_files(&app_path).unwrap(),
+        ["FF8.exe", "FF8_Launcher.exe", "eax.dll"].map(std::path::PathBuf::from)
+    );
+    for (file_name, contents) in [
+        ("eax.dll", "original eax"),
+        ("FF8_Launcher.exe", "original launcher"),
+    ] {
+        assert_eq!(
+            std::fs::read_to_string(app_path.join(file_name)).unwrap(),
+            contents
+        )
+    }
+    assert!(InstallManifest::open(&app_path).unwrap().is_empty());
+}
+
+#[cfg(all(
+    feature = "network",
+    feature = "zip",
+    feature = "pe",
+    feature = "config"
+))]
+#[test]
+fn it_restores_the_patched_game_exe_on_uninstall() {
+    use mumba_core::game::env::Env;
+    use mumba_core::game::installer::Installer;
+    use mumba_core::pe_format;
+
+    let app_path = std::env::temp_dir().join("mumba_test_uninstall_exe");
+    let _ = std::fs::remove_dir_all(&app_path);
+    let exe_path = crate::fixtures::synthetic_pe32(&app_path, 0x0102);
+    let original = std::fs::read(&exe_path).unwrap();
+
+    let mut manifest = InstallManifest::create(&app_path).unwrap();
+    manifest
+        .record(ManifestEntry::Patched(exe_path.clone()))
+        .unwrap();
+    assert!(pe_format::pe_patch_4bg(&exe_path).unwrap());
+    assert_ne!(std::fs::read(&exe_path).unwrap(), original);
+    assert!(pe_format::pe_backup_path(&exe_path).exists());
+
+    let env = Env {
+        cache_dir: app_path.join("cache"),
+        data_dir: app_path.join("data"),
+        config_path: app_path.join("mumba.toml"),
+        mumba_dir: app_path.clone(),
+        ffnx_dir: app_path.clone(),
+        log_path: app_path.join("mumba.log"),
+    };
+    Installer::new(&env, &|_| ()).uninstall(&app_path).unwrap();
+    assert_eq!(std::fs::read(&exe_path).unwrap(), original);
+    assert_eq!(
+        provision::list_files(&app_path).unwrap(),
+        [std::path::PathBuf::from("FF8_EN.exe")]
+    );
+    std::fs::remove_dir_all(&app_path).unwrap();
+}
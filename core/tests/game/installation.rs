@@ -0,0 +1,21 @@
+use mumba_core::game::installation::{Edition, Feature, Installation};
+
+#[test]
+fn it_reads_the_remastered_language_from_steam() {
+    let library = std::env::temp_dir().join("mumba_test_remastered");
+    let _ = std::fs::remove_dir_all(&library);
+    let app_path = library.join("common").join("FINAL FANTASY VIII Remastered");
+    std::fs::create_dir_all(&app_path).unwrap();
+    std::fs::write(app_path.join("FFVIII.exe"), "").unwrap();
+    std::fs::write(
+        library.join("appmanifest_1026680.acf"),
+        "\"AppState\"\n{\n\t\"appid\"\t\t\"1026680\"\n\t\"installdir\"\t\t\"FINAL FANTASY VIII Remastered\"\n\t\"UserConfig\"\n\t{\n\t\t\"language\"\t\t\"french\"\n\t}\n}\n",
+    )
+    .unwrap();
+
+    let installation = Installation::from_exe_path(app_path.join("FFVIII.exe")).unwrap();
+    assert!(matches!(installation.edition, Edition::Remastered));
+    assert_eq!(installation.language, "FR");
+    assert!(!installation.edition.supports(Feature::Ffnx));
+    assert!(!installation.edition.supports(Feature::ReplaceLauncher));
+}
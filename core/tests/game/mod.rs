@@ -1,3 +1,12 @@
+#[cfg(any(feature = "discovery", windows))]
+pub mod discovery;
 #[cfg(feature = "config")]
 pub mod ffnx_config;
+pub mod fingerprint;
 pub mod input_config;
+#[cfg(all(feature = "network", feature = "zip"))]
+pub mod install_journal;
+pub mod install_manifest;
+#[cfg(feature = "steam")]
+pub mod installation;
+pub mod verify;
@@ -0,0 +1,47 @@
+use mumba_core::game::installation::{Edition, Installation};
+use mumba_core::game::verify::{self, Issue};
+
+#[test]
+fn it_reports_missing_and_conflicting_files() {
+    let app_path = std::env::temp_dir().join("mumba_test_verify");
+    let _ = std::fs::remove_dir_all(&app_path);
+    let data_dir = app_path.join("Data").join("lang-fr");
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::write(app_path.join("lang.dat"), "fr").unwrap();
+    for file_name in ["FF8_FR.exe", "FF8_Launcher.exe", "binkw32.dll", "eax.dll"] {
+        std::fs::write(app_path.join(file_name), "").unwrap()
+    }
+    for archive in ["main", "field", "battle", "magic", "menu", "world"] {
+        for extension in ["fs", "fi", "fl"] {
+            std::fs::write(data_dir.join(format!("{}.{}", archive, extension)), "").unwrap()
+        }
+    }
+    std::fs::remove_file(data_dir.join("world.fl")).unwrap();
+    let movies_dir = app_path.join("Data").join("movies");
+    std::fs::create_dir_all(&movies_dir).unwrap();
+    std::fs::write(movies_dir.join("DISC00_00H.BIK"), "").unwrap();
+    std::fs::write(app_path.join("dinput8.dll"), "").unwrap();
+
+    let installation = Installation::from_directory(&app_path, Edition::Steam).unwrap();
+    let report = verify::verify(&installation, &app_path.join("mumba")).unwrap();
+    assert_eq!(report.checked_files, 24);
+    assert_eq!(
+        report.issues,
+        [
+            Issue::Missing(data_dir.join("world.fl")),
+            Issue::Conflict {
+                path: app_path.join("dinput8.dll"),
+                reason: "input hook of another mod"
+            }
+        ]
+    );
+
+    let files = verify::parse("steam Data/movies/*.bik\nsteam Data/movies/*.cam\n").unwrap();
+    let report = verify::verify_with(&files, &installation, &app_path).unwrap();
+    assert_eq!(
+        report.issues,
+        [Issue::Missing(app_path.join("Data/movies/*.cam"))]
+    );
+    assert!(verify::parse("steam Data/movies/*.bik sha256=0\n").is_err());
+    std::fs::remove_dir_all(&app_path).unwrap();
+}
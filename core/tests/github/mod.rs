@@ -0,0 +1,159 @@
+use mumba_core::github::{self, GitHubRelease, GitHubReleaseAsset};
+
+const SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+#[test]
+fn it_parses_checksum_files() {
+    let content = format!(
+        "{}  FFNx-Steam-v1.22.0.zip\n{} *FFNx-FF8_2000-v1.22.0.zip\n",
+        "0".repeat(64),
+        SHA256.to_ascii_uppercase()
+    );
+    assert_eq!(
+        github::parse_checksum_file(&content, "FFNx-FF8_2000-v1.22.0.zip"),
+        Some(String::from(SHA256))
+    );
+    assert_eq!(github::parse_checksum_file(&content, "FFNx.zip"), None);
+    assert_eq!(
+        github::parse_checksum_file(&format!("{}\n", SHA256), "FFNx.zip"),
+        Some(String::from(SHA256))
+    );
+    assert_eq!(github::parse_checksum_file("not a hash", "FFNx.zip"), None);
+}
+
+#[test]
+fn it_uses_asset_digest() {
+    let asset = GitHubReleaseAsset {
+        browser_download_url: String::from("https://example.com/FFNx-Steam-v1.22.0.zip"),
+        name: String::from("FFNx-Steam-v1.22.0.zip"),
+        size: Some(1024),
+        digest: Some(format!("sha256:{}", SHA256)),
+    };
+    let release = GitHubRelease {
+        tag_name: String::from("1.22.0"),
+        id: 1,
+        assets: vec![asset.clone()],
+        prerelease: false,
+        draft: false,
+        published_at: Some(String::from("2025-01-01T00:00:00Z")),
+        body: None,
+        html_url: String::new(),
+    };
+    let remote = github::remote_file_from_asset(&release, &asset);
+    assert_eq!(remote.url, asset.browser_download_url);
+    assert_eq!(remote.size, Some(1024));
+    assert_eq!(remote.sha256, Some(String::from(SHA256)));
+}
+
+fn parse_releases(json: &str) -> github::LatestRelease {
+    serde_json::from_str::<github::GitHubReleases>(json)
+        .unwrap()
+        .latest_release
+}
+
+fn tag(release: Option<GitHubRelease>) -> Option<String> {
+    release.map(|release| release.tag_name)
+}
+
+#[test]
+fn it_selects_releases_per_channel() {
+    let releases = parse_releases(include_str!("../data/github_releases.json"));
+    assert_eq!(tag(releases.latest), Some(String::from("1.20.1")));
+    assert_eq!(tag(releases.beta), Some(String::from("1.21.0-rc1")));
+    assert_eq!(tag(releases.prerelease), Some(String::from("canary")));
+
+    let releases: Vec<GitHubRelease> =
+        serde_json::from_str(include_str!("../data/github_releases.json")).unwrap();
+    let draft = releases.iter().find(|release| release.draft).unwrap();
+    assert_eq!(draft.published_at, None);
+}
+
+#[test]
+fn it_ignores_drafts_without_date() {
+    let releases = parse_releases(
+        r#"[
+            {"tag_name": "1.21.0-rc2", "id": 3, "assets": [], "prerelease": true, "draft": true},
+            {"tag_name": "1.21.0-rc1", "id": 2, "assets": [], "prerelease": true, "draft": false, "published_at": "2025-02-20T08:00:00Z"},
+            {"tag_name": "1.22.0", "id": 1, "assets": [], "prerelease": false, "draft": true, "published_at": null}
+        ]"#,
+    );
+    assert_eq!(tag(releases.latest), None);
+    assert_eq!(tag(releases.prerelease), Some(String::from("1.21.0-rc1")));
+    assert_eq!(tag(releases.beta), Some(String::from("1.21.0-rc1")));
+}
+
+#[test]
+fn it_falls_back_to_stable_for_beta() {
+    let releases = parse_releases(
+        r#"[
+            {"tag_name": "canary", "id": 3, "assets": [], "prerelease": true, "draft": false, "published_at": "2025-03-10T08:00:00Z"},
+            {"tag_name": "1.20.1", "id": 2, "assets": [], "prerelease": false, "draft": false, "published_at": "2025-01-15T08:00:00Z"},
+            {"tag_name": "1.20.1-rc1", "id": 1, "assets": [], "prerelease": true, "draft": false, "published_at": "2025-01-01T08:00:00Z"}
+        ]"#,
+    );
+    assert_eq!(tag(releases.latest), Some(String::from("1.20.1")));
+    assert_eq!(tag(releases.beta), Some(String::from("1.20.1")));
+}
+
+#[test]
+fn it_lists_releases_since_installed_version() {
+    let releases: Vec<GitHubRelease> =
+        serde_json::from_str(include_str!("../data/github_releases.json")).unwrap();
+    let tags = |target: &str, installed: &str| -> Vec<String> {
+        let target = releases.iter().find(|r| r.tag_name == target).unwrap();
+        github::releases_between(releases.clone(), &installed.parse().unwrap(), target)
+            .into_iter()
+            .map(|release| release.tag_name)
+            .collect()
+    };
+    assert_eq!(tags("1.20.1", "1.19.1"), vec!["1.20.1", "1.20.0"]);
+    assert_eq!(tags("1.20.1", "1.20.1"), Vec::<String>::new());
+    assert_eq!(tags("1.21.0-rc1", "1.20.0"), vec!["1.21.0-rc1", "1.20.1"]);
+    // The canary version is read from its asset names
+    assert_eq!(tags("canary", "1.20.1"), vec!["canary", "1.21.0-rc1"]);
+}
+
+#[test]
+fn it_renders_release_notes() {
+    let releases: Vec<GitHubRelease> =
+        serde_json::from_str(include_str!("../data/github_releases.json")).unwrap();
+    let body = releases[3].body.as_deref().unwrap();
+    assert_eq!(
+        mumba_core::markdown::to_plain_text(body),
+        "Fixes\n• Fix a crash on startup\n• See #123"
+    );
+    assert_eq!(
+        mumba_core::markdown::to_plain_text(
+            "**Bold** and __bold__, `__init__`, snake__case__name and 2 ** 3"
+        ),
+        "Bold and bold, __init__, snake__case__name and 2 ** 3"
+    );
+}
+
+#[test]
+fn it_follows_the_next_page_link() {
+    let link =
+        "<https://api.github.com/repositories/1/releases?per_page=100&page=2>; rel=\"next\", \
+        <https://api.github.com/repositories/1/releases?per_page=100&page=5>; rel=\"last\"";
+    assert_eq!(
+        github::next_page_url(link),
+        Some(String::from(
+            "https://api.github.com/repositories/1/releases?per_page=100&page=2"
+        ))
+    );
+    assert_eq!(
+        github::next_page_url(
+            "<https://api.github.com/repositories/1/releases?per_page=100&page=1>; rel=\"prev\""
+        ),
+        None
+    );
+}
+
+#[test]
+fn it_encodes_tags_in_urls() {
+    assert_eq!(github::encode_path_segment("1.22.0-rc1"), "1.22.0-rc1");
+    assert_eq!(
+        github::encode_path_segment("release/1.22 #2"),
+        "release%2F1.22%20%232"
+    );
+}
@@ -1,5 +1,22 @@
 extern crate mumba_core;
 
+#[cfg(feature = "config")]
+mod config;
+mod download_cache;
+#[cfg(feature = "pe")]
+mod fixtures;
 mod game;
+#[cfg(feature = "network")]
+mod github;
 #[cfg(feature = "iro")]
 mod iro;
+#[cfg(feature = "pe")]
+mod pe_format;
+#[cfg(feature = "zip")]
+mod provision;
+#[cfg(feature = "network")]
+mod release_source;
+#[cfg(all(feature = "network", feature = "config"))]
+mod self_update;
+mod steam;
+mod version;
@@ -0,0 +1,114 @@
+use crate::fixtures::synthetic_pe32;
+use mumba_core::pe_format::{self, PePatch};
+use std::path::Path;
+
+fn characteristics(path: &Path) -> u16 {
+    let bytes = std::fs::read(path).unwrap();
+    u16::from_le_bytes([bytes[0x56], bytes[0x57]])
+}
+
+fn checksum(path: &Path) -> u32 {
+    let bytes = std::fs::read(path).unwrap();
+    u32::from_le_bytes([bytes[0x98], bytes[0x99], bytes[0x9A], bytes[0x9B]])
+}
+
+#[test]
+fn it_patches_then_reverses_the_4gb_patch() {
+    let dir = std::env::temp_dir().join("mumba_test_pe_patch");
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = synthetic_pe32(&dir, 0x0102);
+    let original = std::fs::read(&path).unwrap();
+
+    assert!(pe_format::pe_patch_4bg(&path).unwrap());
+    assert_eq!(characteristics(&path), 0x0122);
+    // Computed with the 16-bit sums of `CheckSumMappedFile`
+    assert_eq!(checksum(&path), 0x4E8D);
+    assert_eq!(
+        std::fs::read(pe_format::pe_backup_path(&path)).unwrap(),
+        original
+    );
+    assert!(!dir.join("FF8_EN.exe.tmp").exists());
+    // Already patched
+    assert!(!pe_format::pe_patch_4bg(&path).unwrap());
+
+    assert!(pe_format::pe_unpatch(&path, PePatch::LARGE_ADDRESS_AWARE).unwrap());
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+    assert!(!pe_format::pe_backup_path(&path).exists());
+    assert!(!pe_format::pe_backup_hash_path(&path).exists());
+    assert!(!pe_format::pe_unpatch(&path, PePatch::LARGE_ADDRESS_AWARE).unwrap());
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_rewrites_the_characteristics_when_the_backup_changed() {
+    let dir = std::env::temp_dir().join("mumba_test_pe_unpatch_changed_backup");
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = synthetic_pe32(&dir, 0x0102);
+
+    assert!(pe_format::pe_patch_4bg(&path).unwrap());
+    std::fs::write(pe_format::pe_backup_path(&path), "not the original").unwrap();
+    assert!(pe_format::pe_unpatch(&path, PePatch::LARGE_ADDRESS_AWARE).unwrap());
+    assert_eq!(characteristics(&path), 0x0102);
+    assert_eq!(
+        std::fs::read(pe_format::pe_backup_path(&path)).unwrap(),
+        b"not the original"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_keeps_an_exe_replaced_since_the_patch() {
+    let dir = std::env::temp_dir().join("mumba_test_pe_unpatch_replaced");
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = synthetic_pe32(&dir, 0x0102);
+    let original = std::fs::read(&path).unwrap();
+
+    assert!(pe_format::pe_patch_4bg(&path).unwrap());
+    // A newer version of the game, shipped with the flag set
+    let mut newer = std::fs::read(&path).unwrap();
+    newer[0x200] = 0x90;
+    std::fs::write(&path, &newer).unwrap();
+    assert!(pe_format::pe_unpatch(&path, PePatch::LARGE_ADDRESS_AWARE).unwrap());
+    assert_eq!(characteristics(&path), 0x0102);
+    assert_eq!(std::fs::read(&path).unwrap()[0x200], 0x90);
+    assert_eq!(
+        std::fs::read(pe_format::pe_backup_path(&path)).unwrap(),
+        original
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_folds_the_trailing_bytes_into_the_checksum() {
+    let dir = std::env::temp_dir().join("mumba_test_pe_patch_trailing");
+    let _ = std::fs::remove_dir_all(&dir);
+    let aligned_path = synthetic_pe32(&dir.join("aligned"), 0x0102);
+    let path = synthetic_pe32(&dir, 0x0102);
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes.extend_from_slice(&[0x01, 0x02, 0x03]);
+    std::fs::write(&path, &bytes).unwrap();
+
+    assert!(pe_format::pe_patch_4bg(&aligned_path).unwrap());
+    assert!(pe_format::pe_patch_4bg(&path).unwrap());
+    // The padded dword 0x00030201 folds to 0x0201 + 0x0003, and the size grows by 3
+    assert_eq!(checksum(&path), checksum(&aligned_path) + 0x0204 + 3);
+    assert_eq!(checksum(&path), 0x5094);
+    assert!(pe_format::pe_unpatch(&path, PePatch::LARGE_ADDRESS_AWARE).unwrap());
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_keeps_the_file_when_it_is_not_a_pe32() {
+    let dir = std::env::temp_dir().join("mumba_test_pe_patch_invalid");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("FF8.exe");
+    std::fs::write(&path, b"MZ not a PE file").unwrap();
+
+    assert!(pe_format::pe_patch_4bg(&path).is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), b"MZ not a PE file");
+    assert!(!pe_format::pe_backup_path(&path).exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
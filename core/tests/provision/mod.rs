@@ -0,0 +1,170 @@
+use mumba_core::provision::{self, Progress, RemoteFile};
+use std::cell::RefCell;
+use std::io::Write;
+
+#[test]
+fn it_reports_extract_progress() {
+    let dir = std::env::temp_dir().join("mumba_test_extract_progress");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let zip_path = dir.join("test.zip");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    zip.add_directory("override/", options).unwrap();
+    zip.start_file("override/ff8input.cfg", options).unwrap();
+    zip.write_all(b"Keyboard").unwrap();
+    zip.start_file("FFNx.toml", options).unwrap();
+    zip.write_all(b"show_fps = false").unwrap();
+    zip.finish().unwrap();
+
+    let reported = RefCell::new(Vec::new());
+    let target_dir = dir.join("out");
+    provision::extract_zip(&zip_path, &target_dir, &|progress| {
+        if let Progress::Extract { current, total } = progress {
+            reported.borrow_mut().push((current, total))
+        }
+    })
+    .unwrap();
+
+    assert_eq!(reported.into_inner(), vec![(1, 3), (2, 3), (3, 3)]);
+    assert_eq!(
+        std::fs::read_to_string(target_dir.join("override").join("ff8input.cfg")).unwrap(),
+        "Keyboard"
+    );
+    assert_eq!(
+        std::fs::read_to_string(target_dir.join("FFNx.toml")).unwrap(),
+        "show_fps = false"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn it_keeps_the_executable_bit() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir().join("mumba_test_extract_executable");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let zip_path = dir.join("mumba-linux64.zip");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("mumba", options.unix_permissions(0o755))
+        .unwrap();
+    zip.write_all(b"#!/bin/sh").unwrap();
+    zip.start_file("mumba.toml", options.unix_permissions(0o644))
+        .unwrap();
+    zip.write_all(b"[http]").unwrap();
+    zip.finish().unwrap();
+
+    let target_dir = dir.join("out");
+    provision::extract_zip(&zip_path, &target_dir, &|_| {}).unwrap();
+
+    let mode = |name| {
+        std::fs::metadata(target_dir.join(name))
+            .unwrap()
+            .permissions()
+            .mode()
+            & 0o777
+    };
+    assert_eq!(mode("mumba"), 0o755);
+    assert_eq!(mode("mumba.toml"), 0o644);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_computes_progress_ratio() {
+    let progress = Progress::Download {
+        downloaded: 25,
+        total: Some(100),
+    };
+    assert_eq!(progress.ratio(), Some(0.25));
+    let progress = Progress::Download {
+        downloaded: 25,
+        total: None,
+    };
+    assert_eq!(progress.ratio(), None);
+}
+
+#[test]
+fn it_verifies_size_and_checksum() {
+    let dir = std::env::temp_dir().join("mumba_test_verify_file");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("test.txt");
+    std::fs::write(&path, b"abc").unwrap();
+    let sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    assert_eq!(provision::sha256_file(&path).unwrap(), sha256);
+
+    let mut remote = RemoteFile::new("https://example.com/test.txt");
+    assert!(provision::verify_file(&path, 3, &remote).is_ok());
+    remote.size = Some(3);
+    remote.sha256 = Some(sha256.to_ascii_uppercase());
+    assert!(provision::verify_file(&path, 3, &remote).is_ok());
+    remote.size = Some(4);
+    assert!(matches!(
+        provision::verify_file(&path, 3, &remote),
+        Err(provision::Error::SizeMismatch {
+            expected: 4,
+            actual: 3
+        })
+    ));
+    remote.size = None;
+    remote.sha256 = Some(String::from(
+        "0000000000000000000000000000000000000000000000000000000000000000",
+    ));
+    assert!(matches!(
+        provision::verify_file(&path, 3, &remote),
+        Err(provision::Error::ChecksumMismatch { .. })
+    ));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_swaps_and_restores_directories() {
+    let dir = std::env::temp_dir().join("mumba_test_swap_dir");
+    let _ = std::fs::remove_dir_all(&dir);
+    let target_dir = dir.join("ffnx");
+    let staging_dir = dir.join("staging");
+    let backup_dir = dir.join("backup");
+    std::fs::create_dir_all(target_dir.join("shaders")).unwrap();
+    std::fs::create_dir_all(staging_dir.join("shaders")).unwrap();
+    std::fs::write(target_dir.join("AF3DN.P"), "old").unwrap();
+    std::fs::write(target_dir.join("shaders").join("main.bin"), "old").unwrap();
+    std::fs::write(target_dir.join("save.ff8"), "mine").unwrap();
+    std::fs::write(target_dir.join("shaders").join("custom.bin"), "mine").unwrap();
+    std::fs::write(staging_dir.join("AF3DN.P"), "new").unwrap();
+    std::fs::write(staging_dir.join("shaders").join("main.bin"), "new").unwrap();
+    std::fs::write(staging_dir.join("shaders").join("extra.bin"), "new").unwrap();
+
+    provision::swap_dir(&staging_dir, &target_dir, &backup_dir).unwrap();
+    assert!(!staging_dir.exists());
+    assert_eq!(
+        std::fs::read_to_string(target_dir.join("AF3DN.P")).unwrap(),
+        "new"
+    );
+    assert!(target_dir.join("shaders").join("extra.bin").exists());
+    assert_eq!(
+        std::fs::read_to_string(target_dir.join("save.ff8")).unwrap(),
+        "mine"
+    );
+    assert_eq!(
+        std::fs::read_to_string(target_dir.join("shaders").join("custom.bin")).unwrap(),
+        "mine"
+    );
+
+    provision::restore_backup(&target_dir, &backup_dir).unwrap();
+    assert!(!backup_dir.exists());
+    assert_eq!(
+        std::fs::read_to_string(target_dir.join("AF3DN.P")).unwrap(),
+        "old"
+    );
+    assert_eq!(
+        std::fs::read_to_string(target_dir.join("shaders").join("main.bin")).unwrap(),
+        "old"
+    );
+    assert!(!target_dir.join("shaders").join("extra.bin").exists());
+    assert_eq!(
+        std::fs::read_to_string(target_dir.join("save.ff8")).unwrap(),
+        "mine"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
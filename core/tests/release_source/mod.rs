@@ -0,0 +1,60 @@
+use mumba_core::provision;
+use mumba_core::release_source::{LocalSource, ManifestSource, ReleaseSource};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+fn create_archive(dir: &Path, name: &str, age: u64) {
+    let file = std::fs::File::create(dir.join(name)).unwrap();
+    file.set_modified(SystemTime::now() - Duration::from_secs(age * 3600))
+        .unwrap();
+}
+
+#[test]
+fn it_lists_local_releases() {
+    let dir = std::env::temp_dir().join("mumba_test_local_source");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    create_archive(&dir, "FFNx-Steam-v1.20.1.zip", 48);
+    create_archive(&dir, "FFNx-FF8_2000-v1.20.1.zip", 48);
+    create_archive(&dir, "FFNx-Steam-v1.21.0.zip", 24);
+    create_archive(&dir, "FFNx-Steam-canary-v1.22.0.10.zip", 1);
+    create_archive(&dir, "FFNx-Steam.zip", 0);
+    create_archive(&dir, "SHA256SUMS", 0);
+
+    let source = LocalSource::new(&dir);
+    let releases = source.list_releases().unwrap();
+    let tags: Vec<&str> = releases.iter().map(|r| r.tag_name.as_str()).collect();
+    assert_eq!(tags, vec!["1.22.0.10", "1.21.0", "1.20.1"]);
+    assert_eq!(releases[2].assets.len(), 3);
+    assert!(releases[2]
+        .assets
+        .iter()
+        .all(|asset| provision::local_path(&asset.browser_download_url)
+            .is_some_and(|path| path.exists())));
+
+    let latest = source.latest_releases().unwrap();
+    assert_eq!(
+        latest.latest.map(|r| r.tag_name),
+        Some(String::from("1.21.0"))
+    );
+    assert_eq!(
+        latest.prerelease.map(|r| r.tag_name),
+        Some(String::from("1.22.0.10"))
+    );
+    assert!(source.find_release_by_tag("1.20.1").unwrap().is_some());
+    assert!(source.find_release_by_tag("1.19.0").unwrap().is_none());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn it_reads_a_manifest_file() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/github_releases.json");
+    let source = ManifestSource::new(provision::file_url(&manifest));
+    let latest = source.latest_releases().unwrap();
+    assert_eq!(
+        latest.latest.map(|r| r.tag_name),
+        Some(String::from("1.20.1"))
+    );
+    assert!(source.find_release_by_tag("1.21.0-rc1").unwrap().is_some());
+}
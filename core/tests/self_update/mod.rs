@@ -0,0 +1,70 @@
+use mumba_core::config::UpdateChannel;
+use mumba_core::github::{GitHubRelease, GitHubReleaseAsset, LatestRelease};
+use mumba_core::self_update::{self, Package};
+use mumba_core::version::Version;
+
+fn release(tag: &str, version: &str, prerelease: bool) -> GitHubRelease {
+    let assets = [
+        format!("mumba-{}-setup-windows64-english.msi", version),
+        format!("mumba-{}-setup-windows64-francais.msi", version),
+        format!("mumba-{}-windows64.zip", version),
+        format!("mumba-{}-linux64.zip", version),
+        format!("mumba-{}-linux64.deb", version),
+    ]
+    .into_iter()
+    .map(|name| GitHubReleaseAsset {
+        browser_download_url: format!("https://example.com/{}/{}", tag, name),
+        name,
+        size: Some(1024),
+        digest: None,
+    })
+    .collect();
+    GitHubRelease {
+        tag_name: String::from(tag),
+        id: 1,
+        assets,
+        prerelease,
+        draft: false,
+        published_at: Some(String::from("2025-01-01T00:00:00Z")),
+        body: None,
+        html_url: String::new(),
+    }
+}
+
+fn releases() -> LatestRelease {
+    LatestRelease::from_releases([
+        release("0.2.0", "0.2.0", false),
+        release("continuous", "0.3.0-continuous.12", true),
+    ])
+    .unwrap()
+}
+
+fn find_update(current: &str, package: Package, channel: UpdateChannel) -> Option<String> {
+    let current: Version = current.parse().unwrap();
+    self_update::find_update_in(releases(), &current, package, channel, "fr-FR")
+        .map(|update| update.remote.url)
+}
+
+#[test]
+fn it_finds_mumba_update_per_channel() {
+    assert_eq!(
+        find_update("0.1.0", Package::Msi, UpdateChannel::Stable),
+        Some(String::from(
+            "https://example.com/0.2.0/mumba-0.2.0-setup-windows64-francais.msi"
+        ))
+    );
+    assert_eq!(
+        find_update("0.2.0", Package::Deb, UpdateChannel::Beta),
+        Some(String::from(
+            "https://example.com/continuous/mumba-0.3.0-continuous.12-linux64.deb"
+        ))
+    );
+    assert_eq!(
+        find_update("0.2.0", Package::PortableZip, UpdateChannel::Stable),
+        None
+    );
+    assert_eq!(
+        find_update("0.3.0-continuous.12", Package::Deb, UpdateChannel::Alpha),
+        None
+    );
+}
@@ -0,0 +1,54 @@
+#[cfg(unix)]
+#[test]
+fn it_finds_every_steam_client() {
+    use mumba_core::steam::{steam_roots, SteamClient};
+
+    let home = std::env::temp_dir().join("mumba_test_steam_roots");
+    let _ = std::fs::remove_dir_all(&home);
+    let native = home.join(".local/share/Steam");
+    let flatpak = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+    std::fs::create_dir_all(native.join("steamapps")).unwrap();
+    std::fs::create_dir_all(flatpak.join("steamapps")).unwrap();
+    std::fs::create_dir_all(home.join(".steam")).unwrap();
+    std::os::unix::fs::symlink(&native, home.join(".steam/steam")).unwrap();
+    // Not a Steam root without steamapps
+    std::fs::create_dir_all(home.join("snap/steam/common/.local/share/Steam")).unwrap();
+
+    let roots = steam_roots(&home);
+    assert_eq!(
+        roots,
+        vec![
+            (native.canonicalize().unwrap(), SteamClient::Native),
+            (flatpak.canonicalize().unwrap(), SteamClient::Flatpak)
+        ]
+    );
+}
+
+#[cfg(feature = "steam")]
+#[test]
+fn it_parses_app_manifests() {
+    use mumba_core::steam::{AppManifest, AppState};
+
+    let manifest = AppManifest::parse(
+        r#""AppState"
+{
+	"appid"		"39150"
+	"universe"		"1"
+	"name"		"FINAL FANTASY VIII"
+	"StateFlags"		"1026"
+	"installdir"		"FINAL FANTASY VIII"
+	"buildid"		"5124981"
+	"UserConfig"
+	{
+		"language"		"french"
+	}
+}"#,
+    )
+    .unwrap();
+    assert_eq!(manifest.app_id, 39150);
+    assert_eq!(manifest.install_dir, "FINAL FANTASY VIII");
+    assert_eq!(manifest.build_id, 5124981);
+    assert_eq!(manifest.language.as_deref(), Some("french"));
+    assert_eq!(manifest.state(), AppState::Updating);
+    assert!(AppManifest::parse("\"AppState\" { \"name\" \"FF8\" }").is_err());
+}
@@ -0,0 +1,23 @@
+use mumba_core::version::Version;
+
+fn version(s: &str) -> Version {
+    s.parse().unwrap()
+}
+
+#[test]
+fn it_orders_versions() {
+    assert!(version("1.21.0") > version("1.20.1"));
+    assert!(version("v1.10.0") > version("1.9.9"));
+    assert_eq!(version("1.20"), version("1.20.0"));
+    assert!(version("0.1.0") > version("0.1.0-continuous.42"));
+    assert!(version("0.1.0-continuous.42") > version("0.1.0-continuous.9"));
+    assert!(version("1.0.0-rc.1") > version("1.0.0-beta.2"));
+    assert_eq!(
+        version("0.2.0-continuous.7+abc").to_string(),
+        "0.2.0-continuous.7"
+    );
+    assert!("canary".parse::<Version>().is_err());
+    // FFNx canary builds have a fourth number
+    assert!(version("1.21.0.12") > version("1.21.0"));
+    assert_eq!(version("1.20.1.0"), version("1.20.1"));
+}
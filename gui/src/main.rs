@@ -1,6 +1,6 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 slint::include_modules!();
 use log::error;
 use mumba_core::config::{Config, UpdateChannel};
@@ -21,10 +21,33 @@ fn pos_to_language(language: i32) -> String {
     }
 }
 
+fn pick_zip(ui: &AppWindow, old_path: &Path) -> Option<PathBuf> {
+    let mut dialog = rfd::FileDialog::new();
+    dialog = dialog.set_title("Select a FFNx archive");
+    dialog = dialog.add_filter("ZIP files", &["zip"]);
+    dialog = dialog.set_parent(&ui.window().window_handle());
+
+    if old_path.exists() {
+        dialog = dialog.set_directory(old_path);
+        dialog = dialog.set_file_name(old_path.file_name().unwrap_or_default().to_string_lossy());
+    }
+
+    dialog.pick_file()
+}
+
 fn main() -> Result<(), slint::PlatformError> {
     let env = Env::new("mumba").unwrap();
     mumba_core::mumba_log::init(&env.log_path);
+    mumba_core::self_update::remove_backup();
     let config = Config::from_file(&env.config_path).unwrap_or_else(|_| Config::new());
+    match config.http_config() {
+        Ok(http_config) => {
+            if let Err(e) = mumba_core::http::init(&http_config) {
+                error!("Invalid HTTP configuration: {}", e)
+            }
+        }
+        Err(e) => error!("Invalid HTTP configuration: {}", e),
+    }
     let language = config.language().ok();
     let i18n = I18n::new(language.clone());
 
@@ -59,14 +82,21 @@ fn main() -> Result<(), slint::PlatformError> {
 
     ui.global::<Installations>().on_setup({
         let tx = worker.tx.clone();
-        move |path, update_channel, language| {
+        move |path, update_channel, language, ffnx_zip_path| {
             let update_channel = match update_channel {
                 1 => UpdateChannel::Beta,
                 2 => UpdateChannel::Alpha,
                 _ => UpdateChannel::Stable,
             };
             let language = pos_to_language(language);
-            if let Err(e) = tx.send(worker::Message::Setup(path, update_channel, language)) {
+            let ffnx_zip_path = Some(PathBuf::from(ffnx_zip_path.as_str()))
+                .filter(|path| !path.as_os_str().is_empty());
+            if let Err(e) = tx.send(worker::Message::Setup(
+                path,
+                update_channel,
+                language,
+                ffnx_zip_path,
+            )) {
                 error!("Error: {}", e)
             }
         }
@@ -113,6 +143,14 @@ fn main() -> Result<(), slint::PlatformError> {
         }
     });
 
+    ui.global::<Installations>().on_browse_ffnx_zip({
+        let ui = ui.as_weak();
+        move |old_path| match pick_zip(&ui.unwrap(), Path::new(old_path.as_str())) {
+            Some(new_path) => new_path.to_string_lossy().to_string().into(),
+            None => old_path,
+        }
+    });
+
     ui.global::<Installations>().on_browse_ffnx({
         let ui = ui.as_weak();
         move |old_path| {
@@ -149,6 +187,37 @@ fn main() -> Result<(), slint::PlatformError> {
         move || tx.send(worker::Message::UpdateGame).unwrap()
     });
 
+    ui.global::<Installations>().on_confirm_upgrade_ffnx({
+        let tx = worker.tx.clone();
+        move || tx.send(worker::Message::ConfirmUpdateGame).unwrap()
+    });
+
+    ui.global::<Installations>().on_reinstall_ffnx({
+        let tx = worker.tx.clone();
+        move || tx.send(worker::Message::ReinstallGame).unwrap()
+    });
+
+    ui.global::<Installations>().on_upgrade_ffnx_from_zip({
+        let ui = ui.as_weak();
+        let tx = worker.tx.clone();
+        move || {
+            if let Some(zip_path) = pick_zip(&ui.unwrap(), Path::new("")) {
+                tx.send(worker::Message::UpdateGameFromZip(zip_path))
+                    .unwrap()
+            }
+        }
+    });
+
+    ui.global::<Installations>().on_rollback_ffnx({
+        let tx = worker.tx.clone();
+        move || tx.send(worker::Message::RollbackFfnx).unwrap()
+    });
+
+    ui.global::<Installations>().on_uninstall_ffnx({
+        let tx = worker.tx.clone();
+        move || tx.send(worker::Message::UninstallFfnx).unwrap()
+    });
+
     ui.global::<Installations>().on_set_ffnx_config_bool({
         let tx = worker.tx.clone();
         move |key, value| {
@@ -185,11 +254,22 @@ fn main() -> Result<(), slint::PlatformError> {
             }
         });
 
+    ui.global::<Installations>().on_select_profile({
+        let tx = worker.tx.clone();
+        move |name| tx.send(worker::Message::SelectProfile(name)).unwrap()
+    });
+
     ui.global::<Installations>().on_open_logs({
         let tx = worker.tx.clone();
         move || tx.send(worker::Message::OpenLogs).unwrap()
     });
 
+    ui.global::<Installations>().on_open_url(|url| {
+        if let Err(e) = opener::open_browser(url.as_str()) {
+            error!("Cannot open {}: {}", url, e)
+        }
+    });
+
     ui.run()?;
 
     worker.join().unwrap();
@@ -3,7 +3,9 @@ use crate::lazy_ffnx_config::LazyFfnxConfig;
 use crate::TextLevel;
 use mumba_core::config::UpdateChannel;
 use mumba_core::game::ffnx_config;
+use mumba_core::game::installer::{InstallEvent, InstallListener};
 use mumba_core::i18n::I18n;
+use mumba_core::provision::Progress;
 use mumba_core::screen::Screen;
 use slint::ComponentHandle;
 
@@ -24,6 +26,10 @@ impl UiHelper {
         Self { handle, i18n }
     }
 
+    pub fn handle(&self) -> slint::Weak<AppWindow> {
+        self.handle.clone()
+    }
+
     pub fn clear_task_text(&self) {
         self.handle
             .clone()
@@ -47,6 +53,63 @@ impl UiHelper {
             .unwrap_or_default()
     }
 
+    pub fn set_ffnx_backup(&self, has_backup: bool) {
+        self.handle
+            .clone()
+            .upgrade_in_event_loop(move |h| {
+                h.global::<Installations>().set_has_ffnx_backup(has_backup)
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn set_changelog(&self, changelog: String) {
+        self.handle
+            .clone()
+            .upgrade_in_event_loop(move |h| {
+                h.global::<Installations>()
+                    .set_changelog(slint::SharedString::from(changelog))
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn set_ffnx_update(&self, version: String) {
+        self.handle
+            .clone()
+            .upgrade_in_event_loop(move |h| {
+                h.global::<Installations>()
+                    .set_ffnx_update_version(slint::SharedString::from(version))
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn set_mumba_update(&self, version: String, url: String) {
+        self.handle
+            .clone()
+            .upgrade_in_event_loop(move |h| {
+                let installations = h.global::<Installations>();
+                installations.set_mumba_update_version(slint::SharedString::from(version));
+                installations.set_mumba_update_url(slint::SharedString::from(url))
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn set_progress(&self, progress: Option<Progress>) {
+        let (visible, indeterminate, ratio) = match progress.map(|p| p.ratio()) {
+            Some(Some(ratio)) => (true, false, ratio),
+            Some(None) => (true, true, 0.0),
+            None => (false, false, 0.0),
+        };
+        self.handle
+            .clone()
+            .upgrade_in_event_loop(move |h| {
+                let installations = h.global::<Installations>();
+                installations.set_progress_visible(visible);
+                installations.set_progress_indeterminate(indeterminate);
+                installations.set_progress(ratio)
+            })
+            .unwrap_or_default()
+    }
+
     pub fn set_game_ready(&self, ready: bool) {
         self.handle
             .clone()
@@ -63,6 +126,35 @@ impl UiHelper {
             .unwrap_or_default()
     }
 
+    pub fn set_steam_client(&self, text: String) {
+        self.handle
+            .clone()
+            .upgrade_in_event_loop(move |h| {
+                h.global::<Installations>()
+                    .set_steam_client(slint::SharedString::from(text))
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn set_profiles(&self, profiles: Vec<String>, current_profile: &str) {
+        let current_profile = profiles
+            .iter()
+            .position(|profile| profile == current_profile)
+            .unwrap_or_default() as i32;
+        self.handle
+            .clone()
+            .upgrade_in_event_loop(move |h| {
+                let profiles: Vec<slint::SharedString> =
+                    profiles.iter().map(slint::SharedString::from).collect();
+                let installations = h.global::<Installations>();
+                installations.set_profiles(slint::ModelRc::<slint::SharedString>::from(
+                    profiles.as_slice(),
+                ));
+                installations.set_current_profile(current_profile)
+            })
+            .unwrap_or_default()
+    }
+
     pub fn set_game_exe_path(&self, text: String) {
         self.handle
             .clone()
@@ -214,3 +306,49 @@ impl UiHelper {
         config2
     }
 }
+
+impl InstallListener for UiHelper {
+    fn on_event(&self, event: InstallEvent) {
+        if let InstallEvent::Progress(progress) = event {
+            self.set_progress(Some(progress));
+            return;
+        }
+        self.set_progress(None);
+        match event {
+            InstallEvent::CheckingUpdate => {
+                self.set_task_text(TextLevel::Info, "message-info-check-ffnx-update")
+            }
+            InstallEvent::InstallingFfnx => {
+                self.set_task_text(TextLevel::Info, "message-info-install-in-progress-ffnx")
+            }
+            InstallEvent::UpgradingFfnx => {
+                self.set_task_text(TextLevel::Info, "message-info-upgrade-in-progress-ffnx")
+            }
+            InstallEvent::RollingBackFfnx => {
+                self.set_task_text(TextLevel::Info, "message-info-rollback-in-progress-ffnx")
+            }
+            InstallEvent::Uninstalling => {
+                self.set_task_text(TextLevel::Info, "message-info-uninstall-in-progress-ffnx")
+            }
+            InstallEvent::ReplacingLauncher => {
+                self.set_task_text(TextLevel::Info, "message-info-replace-launcher")
+            }
+            InstallEvent::CopyingGameFiles => {
+                self.set_task_text(TextLevel::Info, "message-info-copy-game-files")
+            }
+            InstallEvent::InstallingPatch => {
+                self.set_task_text(TextLevel::Info, "message-info-install-patch")
+            }
+            InstallEvent::PatchingExe => {
+                self.set_task_text(TextLevel::Info, "message-info-patch-exe")
+            }
+            InstallEvent::UpToDate(_) => {
+                self.set_task_text(TextLevel::Info, "message-info-ffnx-up-to-date")
+            }
+            InstallEvent::FfnxFound(_)
+            | InstallEvent::ConfiguringFfnx
+            | InstallEvent::Progress(_)
+            | InstallEvent::Finished => {}
+        }
+    }
+}
@@ -1,15 +1,24 @@
 use super::AppWindow;
 use crate::ui_helper::UiHelper;
 use crate::worker_loop::WorkerLoop;
-use log::error;
-use mumba_core::config::UpdateChannel;
+use log::{error, info, warn};
+use mumba_core::config::{Config, UpdateChannel};
+use mumba_core::game::env::Env;
+use mumba_core::game::ffnx_installation::FfnxInstallation;
+use mumba_core::game::installer::{
+    FfnxRelease, InstallError, InstallEvent, Installer, UpdateStatus,
+};
 use mumba_core::i18n;
+use mumba_core::release_source::GitHubSource;
+use mumba_core::self_update;
+use mumba_core::version::Version;
 use slint::ComponentHandle;
+use std::path::PathBuf;
 use std::sync::mpsc::{self, Sender};
 
 #[derive(Debug)]
 pub enum Message {
-    Setup(slint::SharedString, UpdateChannel, String),
+    Setup(slint::SharedString, UpdateChannel, String, Option<PathBuf>),
     SetFfnxPath(slint::SharedString),
     LaunchGame,
     LaunchCW,
@@ -21,6 +30,12 @@ pub enum Message {
     SetFfnxConfigCurrentRefreshRate(i32, i32),
     OpenLogs,
     UpdateGame,
+    ConfirmUpdateGame,
+    UpdateGameFromZip(PathBuf),
+    ReinstallGame,
+    RollbackFfnx,
+    UninstallFfnx,
+    SelectProfile(slint::SharedString),
     Quit,
 }
 
@@ -47,6 +62,10 @@ impl Worker {
                 WorkerLoop::new(rx, env, UiHelper::new(handle_weak, i18n)).run()
             }
         });
+        std::thread::spawn({
+            let handle_weak = ui.as_weak();
+            move || check_mumba_update(handle_weak)
+        });
         Self { tx, thread }
     }
 
@@ -55,3 +74,132 @@ impl Worker {
         self.thread.join()
     }
 }
+
+fn open_config() -> Option<(Env, Config)> {
+    let env = match mumba_core::game::env::Env::new("mumba") {
+        Ok(env) => env,
+        Err(e) => {
+            error!("Cannot initialize environment: {}", e);
+            return None;
+        }
+    };
+    let config = Config::from_file(&env.config_path).unwrap_or_else(|_| Config::new());
+    Some((env, config))
+}
+
+/// Runs aside the worker loop on every start, to not delay the game installation
+fn check_mumba_update(handle_weak: slint::Weak<AppWindow>) {
+    let Some((_, config)) = open_config() else {
+        return;
+    };
+    if !config.check_update_on_startup().unwrap_or(true) {
+        return;
+    }
+    let ui = UiHelper::new(handle_weak, i18n::I18n::new(None));
+    match self_update::find_update(
+        config.update_channel().unwrap_or(UpdateChannel::Stable),
+        &config.language().unwrap_or_default(),
+    ) {
+        Ok(Some(update)) => {
+            info!("Mumba {} is available", update.version);
+            ui.set_mumba_update(update.version.to_string(), update.release_url())
+        }
+        Ok(None) => info!("Mumba {} is up to date", self_update::MUMBA_VERSION),
+        Err(e) => warn!("Cannot check for Mumba updates: {}", e),
+    }
+}
+
+/// Runs aside the worker loop every time a profile is loaded.
+/// FFNx is checked once a day per profile, the last update found is shown meanwhile
+pub fn check_ffnx_update_aside(handle_weak: slint::Weak<AppWindow>) {
+    std::thread::spawn(move || {
+        let Some((env, config)) = open_config() else {
+            return;
+        };
+        if !config.check_update_on_startup().unwrap_or(true) {
+            return;
+        }
+        let ui = UiHelper::new(handle_weak, i18n::I18n::new(None));
+        let Some(ffnx_installation) = installed_ffnx(&config) else {
+            return; // Not installed yet
+        };
+        if !config.is_update_check_due() {
+            show_last_ffnx_update(&config, &ffnx_installation, &ui);
+            return;
+        }
+        let ffnx_update = match check_ffnx_update(&env, &config, &ffnx_installation) {
+            Ok(ffnx_update) => ffnx_update,
+            Err(e) => {
+                // Checked again on the next start
+                warn!("Cannot check for FFNx updates: {}", e);
+                return;
+            }
+        };
+        // Reopened to keep the changes made by the worker loop in the meantime
+        let mut saved_config =
+            Config::from_file(&env.config_path).unwrap_or_else(|_| Config::new());
+        // Another profile may have been selected during the check
+        if saved_config.profile() == config.profile() {
+            if let Some(tag) = &ffnx_update {
+                ui.set_ffnx_update(tag.clone())
+            }
+        }
+        saved_config.use_profile(config.profile());
+        saved_config.set_update_checked();
+        saved_config.set_ffnx_update(ffnx_update.as_deref());
+        if let Err(e) = saved_config.save(&env.config_path) {
+            error!("Cannot save configuration to mumba.toml: {}", e)
+        }
+    });
+}
+
+fn installed_ffnx(config: &Config) -> Option<FfnxInstallation> {
+    let installation = config.installation().ok()??;
+    FfnxInstallation::from_directory(&config.ffnx_dir(&installation), &installation)
+}
+
+/// The tag of the FFNx release to install, None when FFNx is up to date
+fn check_ffnx_update(
+    env: &Env,
+    config: &Config,
+    ffnx_installation: &FfnxInstallation,
+) -> Result<Option<String>, InstallError> {
+    let source = config
+        .release_source()
+        .unwrap_or_else(|_| Box::new(GitHubSource::default()));
+    let listener = |_: InstallEvent| {};
+    Ok(
+        match Installer::new(env, &listener)
+            .with_source(source.as_ref())
+            .update_status(ffnx_installation, &FfnxRelease::from_config(config))?
+        {
+            UpdateStatus::UpdateAvailable(release) => {
+                info!("FFNx {} is available", release.tag_name);
+                Some(release.tag_name)
+            }
+            UpdateStatus::UpToDate => {
+                info!("FFNx {} is up to date", ffnx_installation.version);
+                None
+            }
+            UpdateStatus::DevBuild => {
+                info!("FFNx is a development build, skip the update check");
+                None
+            }
+        },
+    )
+}
+
+/// Unless FFNx was upgraded to this version since
+fn show_last_ffnx_update(config: &Config, ffnx_installation: &FfnxInstallation, ui: &UiHelper) {
+    let Ok(Some(tag)) = config.ffnx_update() else {
+        return;
+    };
+    if tag
+        .parse::<Version>()
+        .is_ok_and(|version| version <= ffnx_installation.version)
+    {
+        return;
+    }
+    info!("FFNx {} is available, found by the last check", tag);
+    ui.set_ffnx_update(tag)
+}
@@ -1,6 +1,6 @@
 use crate::lazy_ffnx_config::LazyFfnxConfig;
 use crate::ui_helper::{Page, UiHelper};
-use crate::worker::Message;
+use crate::worker::{self, Message};
 use crate::TextLevel;
 use log::{error, info, warn};
 use mumba_core::config::{Config, UpdateChannel};
@@ -8,34 +8,67 @@ use mumba_core::game::env::Env;
 use mumba_core::game::ffnx_config;
 use mumba_core::game::ffnx_installation::FfnxInstallation;
 use mumba_core::game::installation;
+use mumba_core::game::installer::{Changelog, FfnxRelease, InstallError, Installer};
+use mumba_core::github::GitHubRelease;
+use mumba_core::markdown;
+use mumba_core::provision;
+use mumba_core::release_source::{GitHubSource, ReleaseSource};
 use mumba_core::screen::Screen;
-use mumba_core::steam::get_steam_exe;
-use mumba_core::{pe_format, provision, toml};
+use mumba_core::steam::{Steam, SteamClient};
+use std::cell::Cell;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::Receiver;
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-pub enum InstallError {
-    #[error("Install error: {0}")]
-    ProvisionError(#[from] provision::Error),
-    #[error("Install error: {0}")]
-    IOError(#[from] std::io::Error),
-    #[error("4GB patch Error: {0}")]
-    PeFormatError(#[from] pe_format::Error),
-    #[error("Configure Error: {0}")]
-    TomlFileError(#[from] toml::FileError),
+
+fn steam_client_name(steam_path: &Path, client: SteamClient) -> String {
+    format!("{} ({:?})", steam_path.to_string_lossy(), client)
+}
+
+fn install_error_message(error: &InstallError, default: &'static str) -> &'static str {
+    match error {
+        InstallError::ProvisionError(provision::Error::RateLimited(_)) => {
+            "message-error-github-rate-limit"
+        }
+        InstallError::UnsupportedEdition(_) => "message-error-unsupported-edition",
+        _ => default,
+    }
+}
+
+/// Reads the FFNx version again once its files were replaced
+fn reload_ffnx_installation(
+    ffnx_installation: &mut FfnxInstallation,
+    installation: &installation::Installation,
+) {
+    match FfnxInstallation::from_directory(&ffnx_installation.path, installation) {
+        Some(reloaded) => *ffnx_installation = reloaded,
+        None => warn!(
+            "Invalid FFNx installation in \"{}\"",
+            ffnx_installation.path.to_string_lossy()
+        ),
+    }
 }
 
 pub struct WorkerLoop {
     rx: Receiver<Message>,
     env: Env,
     ui: UiHelper,
+    /// Archive selected in the setup page to install FFNx offline
+    ffnx_zip: Cell<Option<PathBuf>>,
+    /// The release of the changelog shown, installed once the user confirms
+    changelog_release: Cell<Option<GitHubRelease>>,
+    /// Another profile was selected or FFNx was uninstalled, the game is retrieved again
+    restart: Cell<bool>,
 }
 
 impl WorkerLoop {
     pub fn new(rx: Receiver<Message>, env: Env, ui: UiHelper) -> Self {
-        Self { rx, env, ui }
+        Self {
+            rx,
+            env,
+            ui,
+            ffnx_zip: Cell::new(None),
+            changelog_release: Cell::new(None),
+            restart: Cell::new(false),
+        }
     }
 
     fn open_mumba_config(&self) -> Config {
@@ -55,6 +88,31 @@ impl WorkerLoop {
     }
 
     pub fn run(&mut self) {
+        loop {
+            self.run_profile();
+            if !self.restart.take() {
+                break;
+            }
+        }
+    }
+
+    fn run_profile(&mut self) {
+        self.ui.set_mumba_initialized(false);
+        self.ui.set_game_ready(false);
+        self.ui.set_ffnx_backup(false);
+        self.ui.set_game_exe_path(String::new());
+        let mumba_config = self.open_mumba_config();
+        self.ui
+            .set_profiles(mumba_config.profiles(), mumba_config.profile());
+        worker::check_ffnx_update_aside(self.ui.handle());
+        let steam_clients: Vec<String> = Steam::all()
+            .iter()
+            .map(|steam| steam_client_name(&steam.path, steam.client))
+            .collect();
+        if steam_clients.is_empty() {
+            warn!("Cannot find Steam Client installation")
+        }
+        self.ui.set_steam_client(steam_clients.join(", "));
         let (ffnx_path, mut installation, mut update_channel) = match self.retrieve_installation() {
             Some(installation) => installation,
             None => return, // Exit
@@ -67,6 +125,10 @@ impl WorkerLoop {
             &installation.version,
             &installation.app_path.to_string_lossy()
         );
+        if let Some((steam_path, client)) = &installation.steam_client {
+            self.ui
+                .set_steam_client(steam_client_name(steam_path, *client))
+        }
 
         let mut ffnx_installation = match self.retrieve_ffnx_installation(
             &ffnx_path,
@@ -77,8 +139,15 @@ impl WorkerLoop {
             None => return, // Exit
         };
 
-        self.ui.clear_task_text();
+        if installation.is_updating() {
+            warn!("Steam is updating the game");
+            self.ui
+                .set_task_text(TextLevel::Warn, "message-warning-game-updating");
+        } else {
+            self.ui.clear_task_text();
+        }
         self.ui.set_mumba_initialized(true);
+        self.ui.set_ffnx_backup(ffnx_installation.has_backup());
         self.ui.set_game_ready(true);
         let screen_resolutions = Screen::list_screens_resolutions();
 
@@ -86,16 +155,15 @@ impl WorkerLoop {
         let ui_ffnx_config = self
             .ui
             .set_ffnx_config(&mut ffnx_config, &screen_resolutions);
-        let steam_exe = get_steam_exe().unwrap_or_default();
 
         self.ui
             .set_resolutions(&screen_resolutions, ui_ffnx_config.current_resolution);
 
         for received in &self.rx {
             match received {
-                Message::Setup(exe_path, update_chan, language) => {
+                Message::Setup(exe_path, update_chan, language, ffnx_zip) => {
                     update_channel = update_chan.clone();
-                    installation = match self.setup(&exe_path, update_chan, language) {
+                    installation = match self.setup(&exe_path, update_chan, language, ffnx_zip) {
                         Some(inst) => inst,
                         None => continue,
                     };
@@ -108,33 +176,47 @@ impl WorkerLoop {
                         Some(ffnx_inst) => ffnx_inst,
                         None => continue,
                     };
+                    self.ui.set_ffnx_backup(ffnx_installation.has_backup());
                     self.ui.set_game_ready(true);
                 }
                 Message::SetFfnxPath(_ffnx_path) => (),
-                Message::UpdateGame => {
-                    let _ = ffnx_config.get();
-                    self.upgrade_ffnx(
-                        &ffnx_installation,
-                        &installation.edition,
-                        update_channel.clone(),
-                    );
-                    if let Err(error) = ffnx_config.save() {
-                        error!("Cannot save FFNx configuration: {}", error);
-                        self.ui.set_task_text(
-                            TextLevel::Error,
-                            "message-error-cannot-save-ffnx-config",
-                        )
+                received @ (Message::UpdateGame
+                | Message::ConfirmUpdateGame
+                | Message::UpdateGameFromZip(_)
+                | Message::ReinstallGame) => {
+                    let confirmed = !matches!(received, Message::UpdateGame);
+                    let force = matches!(received, Message::ReinstallGame);
+                    let release = match received {
+                        Message::UpdateGameFromZip(zip_path) => FfnxRelease::LocalZip(zip_path),
+                        Message::ConfirmUpdateGame => match self.changelog_release.take() {
+                            Some(release) => FfnxRelease::Resolved(release),
+                            None => self.ffnx_release(update_channel.clone()),
+                        },
+                        _ => self.ffnx_release(update_channel.clone()),
+                    };
+                    if !confirmed && self.show_changelog(&ffnx_installation, &release) {
+                        continue; // Wait for the confirmation
+                    }
+                    if !self.upgrade_ffnx(&mut ffnx_installation, &installation, &release, force) {
+                        continue; // Keep the error message
+                    }
+                }
+                Message::RollbackFfnx => {
+                    if !self.rollback_ffnx(&mut ffnx_installation, &installation) {
+                        continue; // Keep the error message
+                    }
+                    ffnx_config.clear();
+                    self.ui
+                        .set_ffnx_config(&mut ffnx_config, &screen_resolutions);
+                }
+                Message::UninstallFfnx => {
+                    if self.uninstall_ffnx(&ffnx_installation) {
+                        return; // Restart
                     }
+                    continue; // Keep the error message
                 }
                 Message::LaunchGame => {
-                    ffnx_config.get().set_string(
-                        ffnx_config::CFG_APP_PATH,
-                        if cfg!(unix) {
-                            String::from("..")
-                        } else {
-                            installation.app_path.to_string_lossy().to_string()
-                        },
-                    );
+                    ffnx_config.get().set_app_path(&installation);
                     if let Err(error) = ffnx_config.save() {
                         error!("Cannot save FFNx configuration: {}", error);
                         self.ui.set_task_text(
@@ -142,16 +224,13 @@ impl WorkerLoop {
                             "message-error-cannot-save-ffnx-config",
                         )
                     }
-                    if ffnx_installation
-                        .launch_game(&installation, &steam_exe)
-                        .is_err()
-                    {
+                    if ffnx_installation.launch_game(&installation).is_err() {
                         self.ui
                             .set_task_text(TextLevel::Error, "message-error-cannot-launch-game")
                     }
                 }
                 Message::LaunchCW => {
-                    if installation.launch_cw(&steam_exe).is_err() {
+                    if installation.launch_cw().is_err() {
                         self.ui
                             .set_task_text(TextLevel::Error, "message-error-cannot-launch-game")
                     }
@@ -240,6 +319,11 @@ impl WorkerLoop {
                         )
                     }
                 }
+                Message::SelectProfile(name) => {
+                    if self.select_profile(&name) {
+                        return; // Restart
+                    }
+                }
                 Message::OpenLogs => self.open_logs(),
                 Message::CancelConfigureFfnx => ffnx_config.clear(),
                 Message::Quit => return,
@@ -248,6 +332,25 @@ impl WorkerLoop {
         }
     }
 
+    /// Saves the profile as the current one, returns false when it is already selected
+    fn select_profile(&self, name: &str) -> bool {
+        let mut mumba_config = self.open_mumba_config();
+        if name.is_empty() || name == mumba_config.profile() {
+            return false;
+        }
+        info!("Select profile {}", name);
+        mumba_config.set_current_profile(name);
+        if !self.save_mumba_config(&mumba_config) {
+            return false;
+        }
+        self.ffnx_zip.set(None);
+        self.changelog_release.set(None);
+        self.ui.set_ffnx_update(String::new());
+        self.ui.set_changelog(String::new());
+        self.restart.set(true);
+        true
+    }
+
     fn open_logs(&self) {
         if let Err(e) = opener::open(self.env.log_path.clone()) {
             error!("Cannot open {}: {}", self.env.log_path.to_string_lossy(), e)
@@ -272,17 +375,15 @@ impl WorkerLoop {
             Ok(None) | Err(_) => {
                 let installations = installation::Installation::search();
                 for inst in installations {
-                    match inst.edition {
-                        installation::Edition::Standard | installation::Edition::Steam => {
-                            self.ui
-                                .set_game_exe_path(inst.exe_path().to_string_lossy().to_string());
-                        }
-                        installation::Edition::Remastered => {
-                            warn!(
-                                "Ignore remaster at \"{}\", as The Yellow Mumba is not compatible yet",
-                                inst.app_path.to_string_lossy()
-                            )
-                        }
+                    if inst.edition.supports(installation::Feature::Ffnx) {
+                        self.ui
+                            .set_game_exe_path(inst.exe_path().to_string_lossy().to_string());
+                    } else {
+                        warn!(
+                            "Ignore {:?} edition at \"{}\", as FFNx does not support it",
+                            inst.edition,
+                            inst.app_path.to_string_lossy()
+                        )
                     }
                 }
 
@@ -291,10 +392,7 @@ impl WorkerLoop {
         };
 
         if let Some((installation, update_channel)) = ret {
-            let ffnx_path = match mumba_config.data_path() {
-                Ok(Some(ffnx_path)) => PathBuf::from(ffnx_path),
-                Ok(None) | Err(_) => installation.app_path.join("mumba"),
-            };
+            let ffnx_path = mumba_config.ffnx_dir(&installation);
             self.ui
                 .set_ffnx_path(ffnx_path.to_string_lossy().to_string());
             Some((ffnx_path, installation, update_channel))
@@ -305,171 +403,137 @@ impl WorkerLoop {
 
     fn upgrade_ffnx(
         &self,
-        ffnx_installation: &FfnxInstallation,
-        edition: &installation::Edition,
-        update_channel: UpdateChannel,
-    ) {
-        self.ui
-            .set_task_text(TextLevel::Info, "message-info-check-ffnx-update");
-        let url = FfnxInstallation::find_version_on_github(
-            "julianxhokaxhiu/FFNx",
-            edition,
-            update_channel,
-        );
-        self.ui
-            .set_task_text(TextLevel::Info, "message-info-upgrade-in-progress-ffnx");
+        ffnx_installation: &mut FfnxInstallation,
+        installation: &installation::Installation,
+        release: &FfnxRelease,
+        force: bool,
+    ) -> bool {
         self.ui.set_game_ready(false);
-        match FfnxInstallation::download(url.as_str(), &ffnx_installation.path, &self.env) {
-            Ok(()) => (),
+        let source = self.release_source();
+        let result = Installer::new(&self.env, &self.ui)
+            .with_source(source.as_ref())
+            .upgrade(ffnx_installation, installation, release, force);
+        if let Ok(true) = result {
+            reload_ffnx_installation(ffnx_installation, installation)
+        }
+        self.ui.set_progress(None);
+        self.ui.set_ffnx_backup(ffnx_installation.has_backup());
+        self.ui.set_game_ready(true);
+        match result {
+            Ok(upgraded) => {
+                if upgraded {
+                    self.ui.clear_task_text();
+                }
+                self.ui.set_ffnx_update(String::new());
+                let mut mumba_config = self.open_mumba_config();
+                mumba_config.set_ffnx_update(None);
+                self.save_mumba_config(&mumba_config);
+                // False keeps the up to date message displayed
+                upgraded
+            }
             Err(e) => {
-                error!("Error when installing FFNx: {}", e);
+                error!("Error when upgrading FFNx: {}", e);
+                self.ui.set_task_text(
+                    TextLevel::Error,
+                    install_error_message(&e, "message-error-cannot-upgrade-ffnx"),
+                );
+                false
             }
-        };
-        self.ui.set_game_ready(true);
-        self.ui.clear_task_text()
+        }
     }
 
-    fn install_game_and_ffnx(
-        &self,
-        ffnx_dir: &PathBuf,
-        installation: &installation::Installation,
-        update_channel: UpdateChannel,
-    ) -> Result<FfnxInstallation, InstallError> {
-        let ffnx_installation = match FfnxInstallation::from_directory(ffnx_dir, installation) {
-            Some(ffnx_installation) => {
-                info!(
-                    "Found FFNx version {} at \"{}\"",
-                    if ffnx_installation.version == "0.0.0" {
-                        "dev"
-                    } else {
-                        ffnx_installation.version.as_str()
-                    },
-                    ffnx_installation.path.to_string_lossy()
-                );
-                ffnx_installation
-            }
-            None => {
-                self.ui
-                    .set_task_text(TextLevel::Info, "message-info-install-in-progress-ffnx");
-                let url = FfnxInstallation::find_version_on_github(
-                    "julianxhokaxhiu/FFNx",
-                    &installation.edition,
-                    update_channel,
-                );
-                FfnxInstallation::download(url.as_str(), ffnx_dir, &self.env)?;
-                if let Some(ffnx_installation) =
-                    FfnxInstallation::from_directory(ffnx_dir, installation)
-                {
-                    ffnx_installation
-                } else {
-                    return Err(InstallError::IOError(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        "Invalid FFNx installation",
-                    )));
-                }
+    /// Shows the notes of the releases to install, returns false when there are none
+    fn show_changelog(&self, ffnx_installation: &FfnxInstallation, release: &FfnxRelease) -> bool {
+        self.changelog_release.set(None);
+        let source = self.release_source();
+        let changelog = match Installer::new(&self.env, &self.ui)
+            .with_source(source.as_ref())
+            .changelog(ffnx_installation, release)
+        {
+            Ok(changelog) => changelog,
+            Err(e) => {
+                warn!("Cannot retrieve the FFNx release notes: {}", e);
+                return false;
             }
         };
-
-        let mut lazy_ffnx_config = LazyFfnxConfig::new(&ffnx_installation);
-        let ffnx_config = lazy_ffnx_config.get();
-        ffnx_config.set_bool("show_fps", false);
-        ffnx_config.set_bool("show_renderer_backend", false);
-        ffnx_config.set_bool("show_stats", false);
-        ffnx_config.set_bool("show_version", false);
-        lazy_ffnx_config.save()?;
-
-        let exe_path = ffnx_installation.exe_path();
-
-        if matches!(installation.edition, installation::Edition::Steam) {
-            installation.replace_launcher(&self.env)?
+        let releases = match &changelog {
+            Changelog::UpToDate => return false,
+            Changelog::Upgrade(releases) if releases.is_empty() => return false,
+            Changelog::Upgrade(releases) => releases.as_slice(),
+            Changelog::Downgrade(release) => std::slice::from_ref(release),
         };
+        let changelog_text: Vec<String> = releases
+            .iter()
+            .map(|release| {
+                format!(
+                    "{}\n\n{}",
+                    release.tag_name,
+                    markdown::to_plain_text(release.body.as_deref().unwrap_or_default())
+                )
+            })
+            .collect();
+        self.ui.set_changelog(changelog_text.join("\n\n"));
+        self.changelog_release.set(changelog.target().cloned());
+        true
+    }
 
-        if !exe_path.exists() {
-            provision::copy_file(&installation.exe_path(), &exe_path)?
-        }
+    fn ffnx_release(&self, update_channel: UpdateChannel) -> FfnxRelease {
+        FfnxRelease::new(
+            update_channel,
+            self.open_mumba_config().ffnx_version().unwrap_or_default(),
+        )
+    }
 
-        let bink_dll_path = ffnx_installation.path.join("binkw32.dll");
-        if !bink_dll_path.exists() {
-            if matches!(installation.edition, installation::Edition::Standard) {
-                let file_name = match &installation.version {
-                    Some((installation::Version::V100, publisher)) => match publisher {
-                        installation::Publisher::EaJp => Some("FF8EasqPatch"),
-                        installation::Publisher::EaUs | installation::Publisher::EidosUk => {
-                            Some("FF8SqeaPatch")
-                        }
-                        installation::Publisher::EidosDe => Some("FF8EidosGerV12"),
-                        installation::Publisher::EidosFr => Some("FF8EidosFre"),
-                        installation::Publisher::EidosIt => Some("ff8ngita"),
-                        installation::Publisher::EidosSp => Some("ff8ngspa"),
-                    },
-                    _ => None,
-                };
-                match file_name {
-                    Some(file_name) => {
-                        let url = format!("https://www.ff8.fr/download/programs/{}.zip", file_name);
-                        match provision::download_zip(
-                            url.as_str(),
-                            "FF8Patch1.02.zip",
-                            &ffnx_installation.path,
-                            &self.env,
-                        ) {
-                            Ok(()) => provision::rename_file(
-                                &ffnx_installation.path.join("FF8.exe"),
-                                &exe_path,
-                            )?,
-                            Err(e) => {
-                                error!("Cannot download 1.02 patch: {}", e)
-                            }
-                        }
-                    }
-                    None => {
-                        error!("Cannot detect the language of your game")
-                    }
-                }
-            } else {
-                provision::copy_file(&installation.app_path.join("binkw32.dll"), &bink_dll_path)?;
-            }
+    fn release_source(&self) -> Box<dyn ReleaseSource> {
+        self.open_mumba_config()
+            .release_source()
+            .unwrap_or_else(|e| {
+                error!("Invalid release source in mumba.toml: {}", e);
+                Box::new(GitHubSource::default())
+            })
+    }
+
+    fn rollback_ffnx(
+        &self,
+        ffnx_installation: &mut FfnxInstallation,
+        installation: &installation::Installation,
+    ) -> bool {
+        self.ui.set_game_ready(false);
+        let result = Installer::new(&self.env, &self.ui).rollback(ffnx_installation);
+        if result.is_ok() {
+            reload_ffnx_installation(ffnx_installation, installation)
         }
-        match installation.edition {
-            installation::Edition::Steam => {
-                let eax_dll_path = ffnx_installation.path.join("eax.dll");
-                if !eax_dll_path.exists()
-                    || pe_format::pe_version_info(&eax_dll_path)?
-                        .product_name
-                        .unwrap_or_default()
-                        == "FFNx"
-                {
-                    provision::copy_file(&self.env.mumba_dir.join("eax.dll"), &eax_dll_path)?
-                }
-            }
-            installation::Edition::Standard | installation::Edition::Remastered => {
-                let eax_dll_path = ffnx_installation.path.join("creative_eax.dll");
-                if !eax_dll_path.exists() {
-                    if let Err(e) =
-                        provision::copy_file(&self.env.mumba_dir.join("eax.dll"), &eax_dll_path)
-                    {
-                        warn!("Cannot install creative_eax.dll: {}", e);
-                    }
-                }
+        self.ui.set_ffnx_backup(ffnx_installation.has_backup());
+        self.ui.set_game_ready(true);
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Error when restoring FFNx: {}", e);
+                self.ui
+                    .set_task_text(TextLevel::Error, "message-error-cannot-rollback-ffnx");
+                false
             }
         }
-        if matches!(&installation.edition, installation::Edition::Standard) {
-            let ff8_input = ffnx_installation.path.join("override").join("ff8input.cfg");
-            if !ff8_input.exists() {
-                std::fs::create_dir_all(ffnx_installation.path.join("override"))?;
-                provision::copy_file(&installation.app_path.join("ff8input.cfg"), &ff8_input)
-                    .or_else(|e| {
-                        warn!(
-                            "Error when copying ff8input.cfg, creating a new one instead: {}",
-                            e
-                        );
-                        mumba_core::game::input_config::InputConfig::new(&installation.edition)
-                            .to_file(&ff8_input)
-                    })?
-            }
+    }
+
+    /// Forgets the game of the profile once uninstalled, to select it again in the setup page
+    fn uninstall_ffnx(&self, ffnx_installation: &FfnxInstallation) -> bool {
+        self.ui.set_game_ready(false);
+        let result = Installer::new(&self.env, &self.ui).uninstall(&ffnx_installation.path);
+        if let Err(e) = result {
+            error!("Error when uninstalling FFNx: {}", e);
+            self.ui
+                .set_task_text(TextLevel::Error, "message-error-cannot-uninstall-ffnx");
+            self.ui.set_game_ready(true);
+            return false;
         }
-        pe_format::pe_patch_4bg(&exe_path)?;
-        Ok(ffnx_installation)
+        let mut mumba_config = self.open_mumba_config();
+        mumba_config.clear_installation();
+        self.save_mumba_config(&mumba_config);
+        self.ui
+            .set_task_text(TextLevel::Info, "message-info-ffnx-uninstalled");
+        self.restart.set(true);
+        true
     }
 
     fn setup(
@@ -477,8 +541,10 @@ impl WorkerLoop {
         exe_path: &slint::SharedString,
         update_channel: UpdateChannel,
         language: String,
+        ffnx_zip: Option<PathBuf>,
     ) -> Option<installation::Installation> {
         info!("Setup with EXE path \"{}\"", exe_path);
+        self.ffnx_zip.set(ffnx_zip);
         match installation::Installation::from_exe_path(exe_path.as_str()) {
             Ok(installation) => {
                 let mut mumba_config = self.open_mumba_config();
@@ -513,12 +579,17 @@ impl WorkerLoop {
         loop {
             self.ui.set_current_page(Page::Setup);
             match self.rx.recv() {
-                Ok(Message::Setup(exe_path, update_channel, language)) => {
-                    match self.setup(&exe_path, update_channel.clone(), language) {
+                Ok(Message::Setup(exe_path, update_channel, language, ffnx_zip)) => {
+                    match self.setup(&exe_path, update_channel.clone(), language, ffnx_zip) {
                         Some(installation) => return Some((installation, update_channel)),
                         None => continue,
                     }
                 }
+                Ok(Message::SelectProfile(name)) => {
+                    if self.select_profile(&name) {
+                        return None; // Restart
+                    }
+                }
                 Ok(Message::OpenLogs) => self.open_logs(),
                 Ok(Message::Quit) => return None,
                 msg => {
@@ -548,6 +619,11 @@ impl WorkerLoop {
                     }
                     continue;
                 }
+                Ok(Message::SelectProfile(name)) => {
+                    if self.select_profile(&name) {
+                        return None; // Restart
+                    }
+                }
                 Ok(Message::OpenLogs) => self.open_logs(),
                 Ok(Message::Quit) => return None,
                 msg => {
@@ -567,17 +643,25 @@ impl WorkerLoop {
         update_channel: &mut UpdateChannel,
     ) -> Option<FfnxInstallation> {
         let mut ffnx_dir = ffnx_dir.to_path_buf();
+        let mut ffnx_zip = self.ffnx_zip.take();
         loop {
-            match self.install_game_and_ffnx(&ffnx_dir, installation, update_channel.clone()) {
+            let release = match &ffnx_zip {
+                Some(zip_path) => FfnxRelease::LocalZip(zip_path.clone()),
+                None => self.ffnx_release(update_channel.clone()),
+            };
+            let source = self.release_source();
+            let result = Installer::new(&self.env, &self.ui)
+                .with_source(source.as_ref())
+                .install(&ffnx_dir, installation, &release);
+            self.ui.set_progress(None);
+            match result {
                 Ok(ffnx_installation) => {
                     let mut mumba_config = self.open_mumba_config();
                     mumba_config.set_data_path(&ffnx_dir);
                     self.save_mumba_config(&mumba_config);
                     return Some(ffnx_installation);
                 }
-                Err(InstallError::ProvisionError(provision::Error::IoError(e)))
-                    if e.kind() == std::io::ErrorKind::PermissionDenied =>
-                {
+                Err(e) if e.io_error_kind() == Some(std::io::ErrorKind::PermissionDenied) => {
                     error!("Installation error: {}", e);
                     // Fallback to default path
                     self.ui
@@ -591,13 +675,16 @@ impl WorkerLoop {
                 }
                 Err(e) => {
                     error!("Installation error: {}", e);
-                    self.ui
-                        .set_task_text(TextLevel::Error, "message-error-cannot-install-ffnx");
+                    self.ui.set_task_text(
+                        TextLevel::Error,
+                        install_error_message(&e, "message-error-cannot-install-ffnx"),
+                    );
 
                     match self.go_to_setup_page() {
                         Some((inst, update_chan)) => {
                             *installation = inst;
-                            *update_channel = update_chan
+                            *update_channel = update_chan;
+                            ffnx_zip = self.ffnx_zip.take()
                         }
                         None => return None, // Exit
                     }
@@ -4,6 +4,15 @@ click-for-logs = (click here for more informations)
 
 ## Home
 check-update = Check for updates
+upgrade-from-zip = Install from a zip…
+reinstall-ffnx = Reinstall FFNx
+rollback-ffnx = Restore previous FFNx
+uninstall-ffnx = Uninstall FFNx
+confirm-uninstall-ffnx = Confirm uninstall
+ffnx-update-available = New FFNx version:
+mumba-update-available = New Mumba version:
+changelog-title = What's new in FFNx
+upgrade-ffnx = Upgrade
 configure-app = Configure Mumba…
 configure-game = Game Options…
 launch-game = Launch game
@@ -11,13 +20,19 @@ launch-game = Launch game
 button-ok = Ok
 button-cancel = Cancel
 button-browse = Browse…
+button-select-profile = Switch
 
 setup-page-title = Setup
 select-dir-page-title = Select mods directory
 select-dir-page-description = The game directory is not writable without admin rights, please choose a directory where the application can write mods data
+config-group-profile = Game profile
+config-profile-placeholder = Profile name, like "steam" or "modded"
 config-group-ff8-exe-path = FF8 EXE path
+config-steam-client = Steam client:
 config-group-update-channel = Update Channel
 config-group-ffnx-path = Mods path (must be writable!)
+config-group-ffnx-zip = FFNx archive (optional, to install offline)
+config-ffnx-zip-placeholder = Downloaded from GitHub if empty
 update-channel-stable = Stable
 update-channel-beta = Beta
 update-channel-alpha = Alpha
@@ -49,9 +64,23 @@ config-renderer-backend = Renderer backend
 
 # Messages
 message-error-cannot-install-ffnx = Cannot install FFNx
+message-error-cannot-upgrade-ffnx = Cannot upgrade FFNx
+message-error-github-rate-limit = GitHub limits the number of requests, retry later or set a github_token in mumba.toml
+message-error-unsupported-edition = FFNx does not support this edition of the game
+message-error-cannot-rollback-ffnx = Cannot restore the previous FFNx version
+message-error-cannot-uninstall-ffnx = Cannot uninstall FFNx
 message-info-check-ffnx-update = Check for FFNx update…
+message-info-ffnx-up-to-date = FFNx is up to date
 message-info-upgrade-in-progress-ffnx = Upgrading FFNx…
+message-info-rollback-in-progress-ffnx = Restoring the previous FFNx version…
+message-info-uninstall-in-progress-ffnx = Restoring the game files…
+message-info-ffnx-uninstalled = FFNx is uninstalled, the game is back to its original state
 message-info-install-in-progress-ffnx = Installing FFNx…
+message-info-replace-launcher = Replacing the game launcher…
+message-info-copy-game-files = Copying game files…
+message-info-install-patch = Installing the 1.02 patch…
+message-info-patch-exe = Patching the game executable…
+message-warning-game-updating = Steam is updating the game, wait for the end of the update before launching it
 message-error-cannot-save-mumba-config = Cannot save configuration to mumba.toml
 message-error-cannot-save-ffnx-config = Cannot save configuration to FFNx.toml
 message-error-file-not-found = File not found. See logs for more details.
@@ -4,6 +4,15 @@ click-for-logs = (cliquez ici pour en savoir plus)
 
 ## Home
 check-update = Rechercher une mise à jour
+upgrade-from-zip = Installer depuis un zip…
+reinstall-ffnx = Réinstaller FFNx
+rollback-ffnx = Restaurer l'ancien FFNx
+uninstall-ffnx = Désinstaller FFNx
+confirm-uninstall-ffnx = Confirmer la désinstallation
+ffnx-update-available = Nouvelle version de FFNx :
+mumba-update-available = Nouvelle version de Mumba :
+changelog-title = Nouveautés de FFNx
+upgrade-ffnx = Mettre à jour
 configure-app = Configurer Mumba…
 configure-game = Options de jeu…
 launch-game = Lancer le jeu
@@ -11,13 +20,19 @@ launch-game = Lancer le jeu
 button-ok = Ok
 button-cancel = Annuler
 button-browse = Parcourir…
+button-select-profile = Changer
 
 setup-page-title = Configuration
 select-dir-page-title = Sélection du dossier des mods
 select-dir-page-description = Le dossier du jeu n'est pas modifiable sans droits administrateurs, veuillez choisir un dossier où Mumba peut écrire les données des mods
+config-group-profile = Profil de jeu
+config-profile-placeholder = Nom du profil, par exemple « steam » ou « moddé »
 config-group-ff8-exe-path = Chemin vers l'exécutable FF8
+config-steam-client = Client Steam :
 config-group-update-channel = Canal de mise à jour
 config-group-ffnx-path = Chemin des données des mods (doit être modifiable !)
+config-group-ffnx-zip = Archive FFNx (facultatif, pour installer hors ligne)
+config-ffnx-zip-placeholder = Téléchargée depuis GitHub si vide
 update-channel-stable = Stable
 update-channel-beta = Beta
 update-channel-alpha = Alpha
@@ -49,9 +64,23 @@ config-renderer-backend = Moteur de rendu
 
 # Messages
 message-error-cannot-install-ffnx = Impossible d'installer FFNx
+message-error-cannot-upgrade-ffnx = Impossible de mettre à jour FFNx
+message-error-github-rate-limit = GitHub limite le nombre de requêtes, réessayez plus tard ou renseignez un github_token dans mumba.toml
+message-error-unsupported-edition = FFNx n'est pas compatible avec cette édition du jeu
+message-error-cannot-rollback-ffnx = Impossible de restaurer la version précédente de FFNx
+message-error-cannot-uninstall-ffnx = Impossible de désinstaller FFNx
 message-info-check-ffnx-update = Recherche de mise à jour FFNx…
+message-info-ffnx-up-to-date = FFNx est à jour
 message-info-upgrade-in-progress-ffnx = Mise à jour de FFNx…
+message-info-rollback-in-progress-ffnx = Restauration de la version précédente de FFNx…
+message-info-uninstall-in-progress-ffnx = Restauration des fichiers du jeu…
+message-info-ffnx-uninstalled = FFNx est désinstallé, le jeu est revenu à son état d'origine
 message-info-install-in-progress-ffnx = Installation de FFNx…
+message-info-replace-launcher = Remplacement du lanceur du jeu…
+message-info-copy-game-files = Copie des fichiers du jeu…
+message-info-install-patch = Installation du patch 1.02…
+message-info-patch-exe = Modification de l'exécutable du jeu…
+message-warning-game-updating = Steam met à jour le jeu, attendez la fin de la mise à jour avant de le lancer
 message-error-cannot-save-mumba-config = Impossible de sauvegarder la configuration mumba.toml
 message-error-cannot-save-ffnx-config = Impossible de sauvegarder la configuration FFNx.toml
 message-error-file-not-found = Fichier introuvable. Voir les logs pour plus de détails.